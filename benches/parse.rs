@@ -0,0 +1,308 @@
+// parse.rs - MIT License
+//  MIT License
+//  Copyright (c) 2018 Tyler Laing (ZerothLaw)
+//
+//  Permission is hereby granted, free of charge, to any person obtaining a copy
+//  of this software and associated documentation files (the "Software"), to deal
+//  in the Software without restriction, including without limitation the rights
+//  to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//  copies of the Software, and to permit persons to whom the Software is
+//  furnished to do so, subject to the following conditions:
+//
+//  The above copyright notice and this permission notice shall be included in all
+//  copies or substantial portions of the Software.
+//
+//  THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//  IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//  FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//  AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//  LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//  OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+//  SOFTWARE.
+
+//! Parses long arithmetic token streams to keep an eye on the per-token 
+//! cost of GeneralParser::parse_expr. 
+//! 
+//! The shape benchmarks (left-associative chains, balanced trees and nested 
+//! parentheses) run over a few sizes each, from token vectors built by the 
+//! seeded generators below, so two runs always parse identical input. 
+//! 
+//! The hasher benchmark parses the same input with the rule maps hashed by 
+//! std's RandomState and by an FxHash-style hasher. 
+//! 
+//! Run: 
+//! > cargo bench --bench parse
+
+use std::collections::hash_map::RandomState;
+use std::fmt::{Display, Error, Formatter};
+use std::hash::{BuildHasher, BuildHasherDefault, Hasher};
+
+#[macro_use] extern crate criterion;
+extern crate prattle;
+
+use criterion::{black_box, BenchmarkId, Criterion};
+use prattle::prelude::*;
+use prattle::token::DiscriminantKey;
+
+//Tokens carry owned Strings so that any unnecessary clone in the hot loop 
+// shows up in the measurements. 
+#[derive(Clone, Debug, PartialEq)]
+enum BenchToken {
+    Ident(String), 
+    Add, 
+    Sub, 
+    Mul, 
+    Div,
+    Rem, 
+    Pow,
+    LParens, 
+    RParens,
+}
+
+impl Display for BenchToken {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{:?}", self)
+    }
+}
+
+fn arith_spec() -> ParserSpec<BenchToken> {
+    arith_spec_with(RandomState::new())
+}
+
+fn arith_spec_with<S: BuildHasher + Clone>(hasher: S) -> ParserSpec<BenchToken, DiscriminantKey, S> {
+    let mut spec = ParserSpec::with_key_and_hasher(DiscriminantKey, hasher);
+    spec.add_null_assoc(BenchToken::Ident("".to_string()), PrecedenceLevel::Root, |_, token, _| {
+        Ok(Node::Simple(token))
+    }).unwrap();
+    spec.add_left_associations(vec![BenchToken::Add, BenchToken::Sub], PrecedenceLevel::First, |parser, token, lbp, node| {
+        Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    }).unwrap();
+    spec.add_left_associations(vec![BenchToken::Mul, BenchToken::Div], PrecedenceLevel::Second, |parser, token, lbp, node| {
+        Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    }).unwrap();
+    spec.add_left_assoc(BenchToken::Rem, PrecedenceLevel::Third, |parser, token, lbp, node| {
+        Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    }).unwrap();
+    spec.add_left_assoc(BenchToken::Pow, PrecedenceLevel::Fourth, |parser, token, lbp, node| {
+        Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    }).unwrap();
+    spec.add_null_assoc(BenchToken::LParens, PrecedenceLevel::Root, |parser, _, lbp| {
+        let res = parser.parse_expr(lbp)?;
+        parser.consume(BenchToken::RParens)?;
+        Ok(res)
+    }).unwrap();
+    spec
+}
+
+//The multiply-rotate hash rustc uses internally (FxHash), written out here 
+// rather than pulled in as a dependency
+#[derive(Default)]
+struct FxHasher(u64);
+
+impl FxHasher {
+    fn add(&mut self, word: u64) {
+        self.0 = (self.0.rotate_left(5) ^ word).wrapping_mul(0x517c_c1b7_2722_0a95);
+    }
+}
+
+impl Hasher for FxHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.add(u64::from(byte));
+        }
+    }
+
+    fn write_u64(&mut self, n: u64) {
+        self.add(n);
+    }
+
+    fn write_usize(&mut self, n: usize) {
+        self.add(n as u64);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+//xorshift64, so the generated inputs don't depend on an rng crate or its 
+// version
+struct Generator(u64);
+
+impl Generator {
+    fn new() -> Generator {
+        Generator(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn ident(&mut self) -> BenchToken {
+        BenchToken::Ident(format!("identifier_{}", self.next() % 1000))
+    }
+
+    fn op(&mut self) -> BenchToken {
+        const OPS: [BenchToken; 4] = [BenchToken::Add, BenchToken::Sub, BenchToken::Mul, BenchToken::Div];
+        OPS[(self.next() % OPS.len() as u64) as usize].clone()
+    }
+
+    //a op b op c ... with `operands` operands, all folded to the left
+    fn chain(&mut self, operands: usize) -> Vec<BenchToken> {
+        let mut tokens = vec![self.ident()];
+        for _ in 1..operands {
+            tokens.push(self.op());
+            tokens.push(self.ident());
+        }
+        tokens
+    }
+
+    //A full binary tree of the given depth, every subtree in parentheses so 
+    // the shape doesn't depend on which operators were picked
+    fn balanced(&mut self, depth: usize) -> Vec<BenchToken> {
+        let mut tokens = Vec::new();
+        self.push_balanced(depth, &mut tokens);
+        tokens
+    }
+
+    fn push_balanced(&mut self, depth: usize, tokens: &mut Vec<BenchToken>) {
+        if depth == 0 {
+            tokens.push(self.ident());
+            return;
+        }
+        tokens.push(BenchToken::LParens);
+        self.push_balanced(depth - 1, tokens);
+        tokens.push(self.op());
+        self.push_balanced(depth - 1, tokens);
+        tokens.push(BenchToken::RParens);
+    }
+
+    //((( ... (a op b) op c ... ))) nested `depth` parentheses deep
+    fn nested(&mut self, depth: usize) -> Vec<BenchToken> {
+        let mut tokens = vec![BenchToken::LParens; depth];
+        tokens.push(self.ident());
+        for _ in 0..depth {
+            tokens.push(self.op());
+            tokens.push(self.ident());
+            tokens.push(BenchToken::RParens);
+        }
+        tokens
+    }
+}
+
+//a0 + a1 * a2 - a3 / a4 ... with `count` tokens in total (rounded up to odd)
+fn arith_tokens(count: usize) -> Vec<BenchToken> {
+    operator_tokens(count, &[BenchToken::Add, BenchToken::Mul, BenchToken::Sub, BenchToken::Div])
+}
+
+//a0 ^ a1 % a2 * a3 + a4 ^ ... so every Add unwinds three nested parse_expr 
+// calls, each of which looks at the same Add token on the way out. 
+fn ladder_tokens(count: usize) -> Vec<BenchToken> {
+    operator_tokens(count, &[BenchToken::Pow, BenchToken::Rem, BenchToken::Mul, BenchToken::Add])
+}
+
+fn operator_tokens(count: usize, ops: &[BenchToken]) -> Vec<BenchToken> {
+    let mut tokens = Vec::with_capacity(count + 1);
+    tokens.push(BenchToken::Ident("identifier_0".to_string()));
+    let mut i = 1;
+    while tokens.len() < count {
+        tokens.push(ops[(i - 1) % ops.len()].clone());
+        tokens.push(BenchToken::Ident(format!("identifier_{}", i)));
+        i += 1;
+    }
+    tokens
+}
+
+fn bench_arithmetic(c: &mut Criterion) {
+    let tokens = arith_tokens(100_000);
+    c.bench_function("parse_expr 100k arithmetic tokens", |b| {
+        b.iter(|| {
+            let mut parser = GeneralParser::new(arith_spec(), LexerVec::new(tokens.clone()));
+            black_box(parser.parse()).unwrap()
+        })
+    });
+}
+
+fn bench_precedence_ladder(c: &mut Criterion) {
+    let tokens = ladder_tokens(100_000);
+    c.bench_function("parse_expr 100k tokens over four precedence levels", |b| {
+        b.iter(|| {
+            let mut parser = GeneralParser::new(arith_spec(), LexerVec::new(tokens.clone()));
+            black_box(parser.parse()).unwrap()
+        })
+    });
+}
+
+//Identifiers with a 1KiB payload, where every clone of a token costs an 
+// allocation and a copy. LexerSlice keeps the setup clone out of the timing. 
+fn bench_heavy_tokens(c: &mut Criterion) {
+    let padding = "x".repeat(1024);
+    let tokens: Vec<BenchToken> = arith_tokens(20_000).into_iter().map(|token| match token {
+        BenchToken::Ident(name) => BenchToken::Ident(name + &padding), 
+        op => op
+    }).collect();
+    c.bench_function("parse_expr 20k arithmetic tokens with 1KiB identifiers", |b| {
+        b.iter(|| {
+            let mut parser = GeneralParser::new(arith_spec(), LexerSlice::new(&tokens));
+            black_box(parser.parse()).unwrap()
+        })
+    });
+}
+
+fn bench_hasher(c: &mut Criterion) {
+    let tokens = arith_tokens(100_000);
+    let mut group = c.benchmark_group("rule map hasher");
+    group.bench_function("RandomState", |b| {
+        b.iter(|| {
+            let mut parser = GeneralParser::new(arith_spec(), LexerSlice::new(&tokens));
+            black_box(parser.parse()).unwrap()
+        })
+    });
+    group.bench_function("FxHash", |b| {
+        b.iter(|| {
+            let spec = arith_spec_with(BuildHasherDefault::<FxHasher>::default());
+            let mut parser = GeneralParser::new(spec, LexerSlice::new(&tokens));
+            black_box(parser.parse()).unwrap()
+        })
+    });
+    group.finish();
+}
+
+fn bench_shape(c: &mut Criterion, name: &str, sizes: &[usize], generate: fn(&mut Generator, usize) -> Vec<BenchToken>) {
+    let mut group = c.benchmark_group(name);
+    for &size in sizes {
+        let tokens = generate(&mut Generator::new(), size);
+        group.bench_with_input(BenchmarkId::from_parameter(size), &tokens, |b, tokens| {
+            b.iter(|| {
+                let mut parser = GeneralParser::new(arith_spec(), LexerSlice::new(tokens));
+                black_box(parser.parse()).unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+//Parsing the chain is a loop, but the tree it builds is as deep as the chain 
+// is long, and dropping it recurses once per level (see Node), so the 
+// sizes stop where the default main thread stack would run out
+fn bench_left_chain(c: &mut Criterion) {
+    bench_shape(c, "left-assoc chain", &[100, 1_000, 10_000], Generator::chain);
+}
+
+//Depth 16 is 2^16 operands
+fn bench_balanced_tree(c: &mut Criterion) {
+    bench_shape(c, "balanced tree", &[8, 12, 16], Generator::balanced);
+}
+
+//Every level of nesting is a few frames of recursion, so this stays well 
+// below what the default main thread stack can take
+fn bench_nested_parens(c: &mut Criterion) {
+    bench_shape(c, "nested parens", &[10, 100, 500], Generator::nested);
+}
+
+criterion_group!(benches, bench_arithmetic, bench_precedence_ladder, bench_heavy_tokens, 
+    bench_hasher, bench_left_chain, bench_balanced_tree, bench_nested_parens);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+// kinded_token_spec.rs - MIT License
+//  MIT License
+//  Copyright (c) 2018 Tyler Laing (ZerothLaw)
+// 
+//  Permission is hereby granted, free of charge, to any person obtaining a copy
+//  of this software and associated documentation files (the "Software"), to deal
+//  in the Software without restriction, including without limitation the rights
+//  to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//  copies of the Software, and to permit persons to whom the Software is
+//  furnished to do so, subject to the following conditions:
+// 
+//  The above copyright notice and this permission notice shall be included in all
+//  copies or substantial portions of the Software.
+// 
+//  THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//  IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//  FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//  AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//  LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//  OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+//  SOFTWARE.
+
+//! The token_spec example again, using KindedToken: the token kind is a plain
+//! fieldless enum that only needs derives, and the matched text travels 
+//! alongside it as the lexeme. 
+//! 
+//! Run: 
+//! > cargo run --example kinded_token_spec
+
+#[macro_use] extern crate prattle;
+
+use prattle::prelude::*;
+use prattle::token::{KindedToken, KindKey};
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Kind {
+    Number, Ident, 
+    Add, Sub, 
+    Mul, Div, Mod, 
+    LParens, RParens
+}
+
+type CToken = KindedToken<Kind, String>;
+
+fn token_spec() -> Result<ParserSpec<CToken, KindKey>, SpecificationError<CToken>> {
+    let mut spec = ParserSpec::with_key(KindKey);
+    //Any lexeme works for registration, only the kind is used as the key
+    add_null_assoc!(spec, PrecedenceLevel::Root, (CToken::new(Kind::Number, ""), CToken::new(Kind::Ident, "")) => |_, token, _| {
+        Ok(Node::Simple(token))
+    });
+    add_left_assoc!(spec, PrecedenceLevel::First, (CToken::new(Kind::Add, ""), CToken::new(Kind::Sub, "")) => |parser, token, lbp, node| {
+        Ok(Node::Composite { token, children: vec![node, parser.parse_expr(lbp)?], span: None })
+    });
+    add_left_assoc!(spec, PrecedenceLevel::Second, (CToken::new(Kind::Mul, ""), CToken::new(Kind::Div, ""), CToken::new(Kind::Mod, "")) => |parser, token, lbp, node| {
+        Ok(Node::Composite { token, children: vec![node, parser.parse_expr(lbp)?], span: None })
+    });
+    add_null_assoc!(spec, PrecedenceLevel::First, (CToken::new(Kind::LParens, "")) => |parser, _, lbp| {
+        let res = parser.parse_expr(lbp)?;
+        parser.consume(CToken::new(Kind::RParens, ")"))?;
+        Ok(res)
+    });
+    Ok(spec)
+}
+
+//Whitespace separated words, classified by their first character
+fn tokenize(src: &str) -> Vec<CToken> {
+    src.split_whitespace().map(|word| {
+        let kind = match word {
+            "+" => Kind::Add, 
+            "-" => Kind::Sub, 
+            "*" => Kind::Mul, 
+            "/" => Kind::Div, 
+            "%" => Kind::Mod, 
+            "(" => Kind::LParens, 
+            ")" => Kind::RParens, 
+            w if w.chars().all(|c| c.is_ascii_digit()) => Kind::Number, 
+            _ => Kind::Ident
+        };
+        CToken::new(kind, word)
+    }).collect()
+}
+
+fn main() {
+    let lexer = LexerVec::new(tokenize("a + ( 10 * ( b / 2 ) % 4 ) - c"));
+    let spec = token_spec().expect("Should work.");
+    let mut parser = GeneralParser::new(spec, lexer);
+    let res = parser.parse();
+    println!("{:?}", res);
+}
@@ -0,0 +1,142 @@
+// lower_ast.rs - MIT License
+//  MIT License
+//  Copyright (c) 2018 Tyler Laing (ZerothLaw)
+// 
+//  Permission is hereby granted, free of charge, to any person obtaining a copy
+//  of this software and associated documentation files (the "Software"), to deal
+//  in the Software without restriction, including without limitation the rights
+//  to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//  copies of the Software, and to permit persons to whom the Software is
+//  furnished to do so, subject to the following conditions:
+// 
+//  The above copyright notice and this permission notice shall be included in all
+//  copies or substantial portions of the Software.
+// 
+//  THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//  IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//  FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//  AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//  LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//  OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+//  SOFTWARE.
+
+//! Parses arithmetic into the generic Node tree, then lowers that into a 
+//! small typed Expr enum with node::Lower, and evaluates it. 
+//! 
+//! Run: 
+//! > cargo run --example lower_ast
+
+use std::fmt::{Display, Formatter, Error};
+
+extern crate prattle;
+
+use prattle::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CToken {
+    Number(String), 
+    Add, Sub, 
+    Mul, Div
+}
+
+impl Display for CToken {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            CToken::Number(ref s) => write!(f, "{}", s), 
+            CToken::Add => write!(f, "+"), 
+            CToken::Sub => write!(f, "-"), 
+            CToken::Mul => write!(f, "*"), 
+            CToken::Div => write!(f, "/"), 
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinOp {
+    Add, Sub, Mul, Div
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64), 
+    Binary(BinOp, Box<Expr>, Box<Expr>)
+}
+
+impl Expr {
+    fn eval(&self) -> f64 {
+        match *self {
+            Expr::Number(n) => n, 
+            Expr::Binary(ref op, ref lhs, ref rhs) => {
+                let (lhs, rhs) = (lhs.eval(), rhs.eval());
+                match *op {
+                    BinOp::Add => lhs + rhs, 
+                    BinOp::Sub => lhs - rhs, 
+                    BinOp::Mul => lhs * rhs, 
+                    BinOp::Div => lhs / rhs, 
+                }
+            }
+        }
+    }
+}
+
+impl Lower<CToken> for Expr {
+    fn lower(node: Node<CToken>) -> Result<Expr, LowerError> {
+        match node {
+            Node::Simple(CToken::Number(ref s)) => s.parse()
+                .map(Expr::Number)
+                .map_err(|e| LowerError::Invalid{message: format!("{}: {}", s, e)}), 
+            Node::Composite{token, children, ..} => {
+                let op = match token {
+                    CToken::Add => BinOp::Add, 
+                    CToken::Sub => BinOp::Sub, 
+                    CToken::Mul => BinOp::Mul, 
+                    CToken::Div => BinOp::Div, 
+                    _ => return Err(LowerError::UnexpectedNode{node: token.to_string()})
+                };
+                if children.len() != 2 {
+                    return Err(LowerError::WrongArity{token: token.to_string(), expected: 2, found: children.len()})
+                }
+                let mut children = children.into_iter();
+                let lhs = children.next().unwrap().lower_into()?;
+                let rhs = children.next().unwrap().lower_into()?;
+                Ok(Expr::Binary(op, Box::new(lhs), Box::new(rhs)))
+            }, 
+            node => Err(LowerError::unexpected(&node))
+        }
+    }
+}
+
+fn token_spec() -> Result<ParserSpec<CToken>, SpecificationError<CToken>> {
+    let mut spec = ParserSpec::new();
+    spec.add_null_assoc(CToken::Number("".to_string()), PrecedenceLevel::Root, |_, token, _| {
+        Ok(Node::Simple(token))
+    })?;
+    spec.add_left_associations(vec![CToken::Add, CToken::Sub], PrecedenceLevel::First, |parser, token, lbp, node| {
+        Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    })?;
+    spec.add_left_associations(vec![CToken::Mul, CToken::Div], PrecedenceLevel::Second, |parser, token, lbp, node| {
+        Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    })?;
+    Ok(spec)
+}
+
+fn main() {
+    // 1 + 2 * 3 - 8 / 4
+    let tokens = vec![
+        CToken::Number("1".to_string()), 
+        CToken::Add, 
+        CToken::Number("2".to_string()), 
+        CToken::Mul, 
+        CToken::Number("3".to_string()), 
+        CToken::Sub, 
+        CToken::Number("8".to_string()), 
+        CToken::Div, 
+        CToken::Number("4".to_string()), 
+    ];
+    let spec = token_spec().expect("Should work.");
+    let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+    let node = parser.parse().expect("Should parse.");
+    let expr: Expr = node.lower_into().expect("Should lower.");
+    println!("{:?}", expr);
+    println!("= {}", expr.eval());
+}
@@ -0,0 +1,95 @@
+// named_levels.rs - MIT License
+//  MIT License
+//  Copyright (c) 2018 Tyler Laing (ZerothLaw)
+// 
+//  Permission is hereby granted, free of charge, to any person obtaining a copy
+//  of this software and associated documentation files (the "Software"), to deal
+//  in the Software without restriction, including without limitation the rights
+//  to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//  copies of the Software, and to permit persons to whom the Software is
+//  furnished to do so, subject to the following conditions:
+// 
+//  The above copyright notice and this permission notice shall be included in all
+//  copies or substantial portions of the Software.
+// 
+//  THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//  IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//  FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//  AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//  LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//  OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+//  SOFTWARE.
+
+//! Arithmetic and comparisons with the levels named for what they hold 
+//! (PrecedenceLevel::ADDITIVE and friends), plus a grammar-specific alias, 
+//! instead of bare First, Second, ... 
+//! 
+//! Run: 
+//! > cargo run --example named_levels
+
+use std::fmt::{Display, Formatter, Error};
+
+extern crate prattle;
+
+use prattle::prelude::*;
+
+//This grammar's own name for the level, next to the predefined ones
+const EXPONENT: PrecedenceLevel = PrecedenceLevel::Seventh;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CToken {
+    Var(String), 
+    Less, 
+    Add, 
+    Mul, 
+    Pow
+}
+
+impl Display for CToken {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match *self {
+            CToken::Var(ref s) => write!(f, "{}", s), 
+            CToken::Less => write!(f, "<"), 
+            CToken::Add => write!(f, "+"), 
+            CToken::Mul => write!(f, "*"), 
+            CToken::Pow => write!(f, "^"), 
+        }
+    }
+}
+
+fn binary(parser: &mut dyn Parser<CToken>, token: CToken, rbp: PrecedenceLevel, node: Node<CToken>) -> Result<Node<CToken>, ParseError<CToken>> {
+    Ok(Node::Composite{token, children: vec![node, parser.parse_expr(rbp)?], span: None})
+}
+
+fn token_spec() -> Result<ParserSpec<CToken>, SpecificationError<CToken>> {
+    let mut spec = ParserSpec::new();
+    spec.add_null_assoc(CToken::Var("".to_string()), PrecedenceLevel::Root, |_, token, _| {
+        Ok(Node::Simple(token))
+    })?;
+    spec.add_left_assoc(CToken::Less, PrecedenceLevel::COMPARISON, binary)?;
+    spec.add_left_assoc(CToken::Add, PrecedenceLevel::ADDITIVE, binary)?;
+    spec.add_left_assoc(CToken::Mul, PrecedenceLevel::MULTIPLICATIVE, binary)?;
+    let (operand, bp) = Associativity::Right.binding_powers(EXPONENT);
+    spec.add_left_right_assoc(CToken::Pow, operand, bp, binary)?;
+    Ok(spec)
+}
+
+fn main() {
+    // a + b * c ^ d < e
+    let tokens = vec![
+        CToken::Var("a".to_string()), 
+        CToken::Add, 
+        CToken::Var("b".to_string()), 
+        CToken::Mul, 
+        CToken::Var("c".to_string()), 
+        CToken::Pow, 
+        CToken::Var("d".to_string()), 
+        CToken::Less, 
+        CToken::Var("e".to_string()), 
+    ];
+    let spec = token_spec().expect("Should work.");
+    println!("{}", spec);
+    let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+    let node = parser.parse().expect("Should parse.");
+    println!("{}", node.to_sexpr());
+}
@@ -35,10 +35,10 @@ fn token_spec() -> Result<ParserSpec<CToken>, SpecificationError<CToken>> {
         Ok(Node::Simple(token.clone()))
     });
     add_left_assoc!(spec, PrecedenceLevel::First, (CToken::Add, CToken::Sub) => |parser, token, lbp, node| {
-        Ok(Node::Composite { token: token.clone(), children: vec![node, parser.parse_expr(lbp)?] } )
+        Ok(Node::Composite { token: token.clone(), children: vec![node, parser.parse_expr(lbp)?], span: None } )
     } );
     add_left_assoc!(spec, PrecedenceLevel::Second, (CToken::Mul, CToken::Div, CToken::Mod) => |parser, token, lbp, node| {
-        Ok(Node::Composite { token: token.clone(), children: vec![node, parser.parse_expr(lbp)?] } )
+        Ok(Node::Composite { token: token.clone(), children: vec![node, parser.parse_expr(lbp)?], span: None } )
     } );
     add_null_assoc!(spec, PrecedenceLevel::First, (CToken::LParens) => |parser, _, lbp| {
         let res = parser.parse_expr(lbp)?;
@@ -0,0 +1,99 @@
+// parse.rs - MIT License
+//  MIT License
+//  Copyright (c) 2018 Tyler Laing (ZerothLaw)
+// 
+//  Permission is hereby granted, free of charge, to any person obtaining a copy
+//  of this software and associated documentation files (the "Software"), to deal
+//  in the Software without restriction, including without limitation the rights
+//  to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//  copies of the Software, and to permit persons to whom the Software is
+//  furnished to do so, subject to the following conditions:
+// 
+//  The above copyright notice and this permission notice shall be included in all
+//  copies or substantial portions of the Software.
+// 
+//  THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//  IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//  FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//  AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//  LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//  OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+//  SOFTWARE.
+
+//Feeds arbitrary token streams to an arithmetic grammar with brackets. 
+// Any ParseError is fine; a panic, hang or stack overflow is a bug. 
+//
+// cargo fuzz run parse
+
+#![no_main]
+#[macro_use] extern crate libfuzzer_sys;
+extern crate arbitrary;
+extern crate prattle;
+
+use std::fmt::{Display, Error, Formatter};
+
+use arbitrary::{Arbitrary, Unstructured};
+use prattle::arbitrary_tokens;
+use prattle::prelude::*;
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Tk {
+    Atom(u8), 
+    Add, 
+    Sub, 
+    Mul, 
+    Open, 
+    Close
+}
+
+impl Display for Tk {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl<'a> Arbitrary<'a> for Tk {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Tk> {
+        Ok(match u.int_in_range(0..=5u8)? {
+            0 => Tk::Atom(u.arbitrary()?), 
+            1 => Tk::Add, 
+            2 => Tk::Sub, 
+            3 => Tk::Mul, 
+            4 => Tk::Open, 
+            _ => Tk::Close
+        })
+    }
+}
+
+fn binary(parser: &mut dyn Parser<Tk>, token: Tk, lbp: PrecedenceLevel, node: Node<Tk>) -> Result<Node<Tk>, ParseError<Tk>> {
+    Ok(Node::branch(token, vec![node, parser.parse_expr(lbp)?]))
+}
+
+fn spec() -> ParserSpec<Tk> {
+    let mut spec = ParserSpec::new();
+    spec.add_null_assoc(Tk::Atom(0), PrecedenceLevel::Root, |_, token, _| Ok(Node::leaf(token))).unwrap();
+    spec.add_prefix_and_infix(Tk::Sub, PrecedenceLevel::Third, PrecedenceLevel::First, 
+        |parser, token, rbp| Ok(Node::branch(token, vec![parser.parse_expr(rbp)?])), 
+        binary).unwrap();
+    spec.add_left_assoc(Tk::Add, PrecedenceLevel::First, binary).unwrap();
+    spec.add_left_assoc(Tk::Mul, PrecedenceLevel::Second, binary).unwrap();
+    spec.add_null_exact(Tk::Open, PrecedenceLevel::Root, |parser, _, _| {
+        let node = parser.parse()?;
+        parser.consume(Tk::Close)?;
+        Ok(node)
+    }).unwrap();
+    spec.add_closing(Tk::Close);
+    spec
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut u = Unstructured::new(data);
+    if let Ok(tokens) = arbitrary_tokens::<Tk>(&mut u) {
+        let mut parser = GeneralParser::new(spec(), LexerVec::new(tokens));
+        while !parser.at_end() {
+            if parser.parse().is_err() {
+                break
+            }
+        }
+    }
+});
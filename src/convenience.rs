@@ -0,0 +1,202 @@
+// convenience.rs - MIT License
+//  MIT License
+//  Copyright (c) 2018 Tyler Laing (ZerothLaw)
+// 
+//  Permission is hereby granted, free of charge, to any person obtaining a copy
+//  of this software and associated documentation files (the "Software"), to deal
+//  in the Software without restriction, including without limitation the rights
+//  to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+//  copies of the Software, and to permit persons to whom the Software is
+//  furnished to do so, subject to the following conditions:
+// 
+//  The above copyright notice and this permission notice shall be included in all
+//  copies or substantial portions of the Software.
+// 
+//  THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+//  IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+//  FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+//  AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+//  LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+//  OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+//  SOFTWARE.
+
+//! # Convenience functions
+//! 
+//! One-call wrappers around the lexer/parser wiring, for quick scripts and 
+//! tests where building a LexerVec and a GeneralParser by hand is just noise. 
+
+use std::marker::{Send, Sync};
+
+use errors::ParseError;
+use lexer::LexerVec;
+use node::Node;
+use parser::GeneralParser;
+use spec::ParserSpec;
+use token::{KeyFn, Token};
+
+/// Tokenizes `input` with `tokenize`, then parses a single expression from 
+/// the tokens with `spec`. 
+/// 
+/// The whole input has to be one expression: if tokens are left over after 
+/// it, ParseError::MalformedSyntax is returned with the parsed node and the 
+/// first leftover token. 
+pub fn parse_str<T, K, F, I>(spec: ParserSpec<T, K>, input: &str, tokenize: F) -> Result<Node<T>, ParseError<T>>
+    where T: Token + Send + Sync + 'static, 
+          K: KeyFn<T>, 
+          F: FnOnce(&str) -> I, 
+          I: IntoIterator<Item=T>
+{
+    parse_tokens(spec, tokenize(input))
+}
+
+/// Parses each token vector in `inputs` as a single expression, as parse_str 
+/// does, spread over rayon's thread pool. Each input gets its own clone of 
+/// `spec`, which only bumps the reference counts of its rules. Results come 
+/// back in the order of `inputs`. 
+#[cfg(feature = "rayon")]
+pub fn parse_many_parallel<T, K>(spec: &ParserSpec<T, K>, inputs: Vec<Vec<T>>) -> Vec<Result<Node<T>, ParseError<T>>>
+    where T: Token + Send + Sync + 'static, 
+          K: KeyFn<T>
+{
+    use rayon::prelude::*;
+
+    inputs.into_par_iter()
+        .map(|tokens| parse_tokens(spec.clone(), tokens))
+        .collect()
+}
+
+/// Reads a token stream out of fuzzer input, for running a grammar under 
+/// cargo fuzz (see fuzz/ in the repository): 
+/// 
+/// ```ignore
+/// fuzz_target!(|data: &[u8]| {
+///     let mut u = Unstructured::new(data);
+///     if let Ok(tokens) = arbitrary_tokens::<MyToken>(&mut u) {
+///         let _ = GeneralParser::new(spec(), LexerVec::new(tokens)).parse();
+///     }
+/// });
+/// ```
+#[cfg(feature = "arbitrary")]
+pub fn arbitrary_tokens<'a, T>(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Vec<T>>
+    where T: ::arbitrary::Arbitrary<'a>
+{
+    u.arbitrary_iter()?.collect()
+}
+
+fn parse_tokens<T, K, I>(spec: ParserSpec<T, K>, tokens: I) -> Result<Node<T>, ParseError<T>>
+    where T: Token + Send + Sync + 'static, 
+          K: KeyFn<T>, 
+          I: IntoIterator<Item=T>
+{
+    let lexer = LexerVec::new(tokens);
+    let mut parser = GeneralParser::new(spec, lexer);
+    let node = parser.parse()?;
+    match parser.peek_token().cloned() {
+        Some(token) => Err(ParseError::MalformedSyntax{node: Box::new(node), token}), 
+        None => Ok(node)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt::{Display, Error, Formatter};
+
+    use super::*;
+    use precedence::PrecedenceLevel;
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Tk {
+        Atom(char), 
+        Add
+    }
+
+    impl Display for Tk {
+        fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+            match *self {
+                Tk::Atom(c) => write!(f, "{}", c), 
+                Tk::Add => write!(f, "+")
+            }
+        }
+    }
+
+    fn arith_spec() -> ParserSpec<Tk> {
+        let mut spec = ParserSpec::new();
+        spec.add_null_assoc(Tk::Atom(' '), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc(Tk::Add, PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec
+    }
+
+    //Every non-whitespace char is a token
+    fn chars(src: &str) -> Vec<Tk> {
+        src.chars().filter(|c| !c.is_whitespace()).map(|c| match c {
+            '+' => Tk::Add, 
+            c => Tk::Atom(c)
+        }).collect()
+    }
+
+    #[test]
+    fn test_parse_str() {
+        assert_eq!(parse_str(arith_spec(), "a + b", chars), 
+            Ok(Node::Composite{token: Tk::Add, children: vec![Node::Simple(Tk::Atom('a')), Node::Simple(Tk::Atom('b'))], span: None}));
+        assert_eq!(parse_str(arith_spec(), "a +", chars), Err(ParseError::UnexpectedEof));
+        assert_eq!(parse_str(arith_spec(), "", chars), Err(ParseError::EmptyInput));
+    }
+
+    #[test]
+    fn test_parse_str_leftover_tokens() {
+        assert_eq!(parse_str(arith_spec(), "a b", chars), 
+            Err(ParseError::MalformedSyntax{node: Box::new(Node::Simple(Tk::Atom('a'))), token: Tk::Atom('b')}));
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn test_parse_many_parallel_matches_sequential() {
+        let inputs: Vec<Vec<Tk>> = (0..100u8)
+            .map(|i| match i % 3 {
+                0 => chars("a + b + c"), 
+                1 => chars("x"), 
+                _ => chars("a b")
+            })
+            .collect();
+        let sequential: Vec<_> = inputs.iter()
+            .map(|tokens| parse_tokens(arith_spec(), tokens.clone()))
+            .collect();
+        assert_eq!(parse_many_parallel(&arith_spec(), inputs), sequential);
+    }
+
+    #[cfg(feature = "arbitrary")]
+    impl<'a> ::arbitrary::Arbitrary<'a> for Tk {
+        fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Tk> {
+            Ok(match u.int_in_range(0..=3u8)? {
+                0 => Tk::Add, 
+                _ => Tk::Atom(*u.choose(&['a', 'b', ' '])?)
+            })
+        }
+    }
+
+    //Stands in for cargo fuzz: the parser must hand back an error, never panic
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_tokens_never_panic() {
+        use arbitrary::Unstructured;
+
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        for len in 0..256 {
+            let data: Vec<u8> = (0..len).map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            }).collect();
+            let mut u = Unstructured::new(&data);
+            let tokens = arbitrary_tokens::<Tk>(&mut u).unwrap();
+            let _ = parse_tokens(arith_spec(), tokens);
+
+            let mut u = Unstructured::new(&data);
+            let node: Node<Tk> = ::arbitrary::Arbitrary::arbitrary(&mut u).unwrap();
+            let _ = node.to_string();
+        }
+    }
+}
@@ -48,24 +48,117 @@ use token::Token;
 pub enum ParseError<T: Token + Send + Sync + 'static> {
     /// # ParseError::MalformedSyntax
     /// Represents parser context when a syntax rule fails.
-    /// Not returned by the general parser implementation. 
+    /// Not returned by the general parser implementation; convenience::parse_str 
+    /// returns it when tokens are left over after the expression, and rules 
+    /// added with ParserSpec::add_left_assoc_matching when no matcher fits. 
     /// Usage: during a syntax rule, if this error is to be 
     /// returned, use *node* for the current node passed to
     /// the syntax rule, and *token* for the token that lead to
     /// the error to be returned.
     #[fail(display = "incorrect syntax, failed on node: {} with token: {}", node, token)]
-    MalformedSyntax{ node: Node<T>, token: T }, 
+    MalformedSyntax{ node: Box<Node<T>>, token: T }, 
     /// Returned by the parser when a rule is not found for a specific token.
     /// Generally only should be seen during development of a language spec.
     #[fail(display = "missing a {} syntax rule for: {}", ty, token)]
     MissingRule {token: T, ty: String}, 
-    /// Expected more input than was available. Returned by the parser.
+    /// The lexer had no tokens at all, so there was nothing to parse: the 
+    /// user entered nothing, as opposed to a broken expression. Returned by 
+    /// the parser when the very first expression finds the input empty. 
+    #[fail(display = "no input to parse")]
+    EmptyInput, 
+    /// There were no tokens left to start an expression with: the input 
+    /// ended cleanly, between expressions. Returned by the parser; whether 
+    /// it's an error is up to the caller (Parser::at_end checks beforehand). 
     #[fail(display = "token iteration ended before parsing context finished")]
     Incomplete, 
+    /// The input ran out partway through an expression, or where consume 
+    /// expected a token. Returned by the parser. 
+    #[fail(display = "unexpected end of input")]
+    UnexpectedEof, 
     /// <P as Parser<T>>::consume(end_token: T) was called, and the required
     /// token was not found as the next token(returned by peek/next_token).
     #[fail(display = "parser.consume(end_token: {}) didn't find expected token, instead found: {}.", expected, found)]
-    ConsumeFailed {expected: T, found: T}
+    ConsumeFailed {expected: T, found: T}, 
+    /// <P as Parser<T>>::consume_one_of(tokens: &[T]) was called, and the next 
+    /// token wasn't any of *expected*. 
+    #[fail(display = "parser.consume_one_of(tokens: {:?}) didn't find any of the expected tokens, instead found: {}.", expected, found)]
+    ConsumeOneFailed {expected: Vec<T>, found: T}, 
+    /// <P as Parser<T>>::consume_if(matcher) was called, and the matcher 
+    /// rejected the next token, *found*. 
+    #[fail(display = "parser.consume_if(matcher) didn't match the next token: {}.", found)]
+    ConsumeIfFailed {found: T}, 
+    /// A token registered as closing (see ParserSpec::add_closing) came 
+    /// where an expression had to start, as in ```()```. 
+    #[fail(display = "unexpected closing token {} where an operand was expected", token)]
+    UnexpectedClosing {token: T}, 
+    /// The lexer's error token (see ParserSpec::set_error_token) came where 
+    /// an expression had to start: the input had something in it the lexer 
+    /// couldn't make a token of. 
+    #[fail(display = "lexical error at: {}", token)]
+    LexicalError {token: T}, 
+    /// <P as Parser<T>>::consume_matching(open) was called, and the next 
+    /// token, *found*, wasn't *expected*, the close token registered for 
+    /// *open* (see ParserSpec::add_delimiters). 
+    #[fail(display = "mismatched delimiter: {} expects {} but found {}", open, expected, found)]
+    MismatchedDelimiter {open: T, expected: T, found: T}, 
+    /// An opening token (see ParserSpec::add_opening) was reached with 
+    /// *limit* of them already open, the maximum set with 
+    /// ParserSpec::set_max_nesting. 
+    #[fail(display = "brackets nested deeper than the limit of {}", limit)]
+    NestingTooDeep {limit: usize}, 
+    /// A parsing loop (parse_repeated, parse_sequence) parsed an item but the
+    /// Lexer's position didn't move, so looping again would never end. 
+    /// *token* is the token the loop is stuck on. 
+    #[fail(display = "parsing made no progress, stuck at: {}", token)]
+    NoProgress {token: T}, 
+    /// parse_sequence_bounded parsed *limit* items and more followed. 
+    #[fail(display = "sequence is longer than the limit of {} items", limit)]
+    SequenceTooLong {limit: usize}, 
+    /// <P as Parser<T>>::splice(tokens) was called on a parser whose lexer 
+    /// can't insert tokens (see Lexer::splice). 
+    #[fail(display = "the lexer doesn't support splice")]
+    SpliceUnsupported
+}
+
+/// # LexError
+/// Errors from lexers themselves rather than from parsing. 
+/// 
+/// LexError::NoMatch is returned by StrLexer when its closure doesn't 
+/// tokenize the input at *offset*, and so by LexerVec::from_regex when none 
+/// of its rules match there (a byte offset, on *line* and *column* counted 
+/// from 1, the column in chars unless the StrLexer's ColumnUnit says bytes). 
+#[derive(Clone, Debug, Eq, Fail, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LexError {
+    #[fail(display = "no lexer rule matches the input at line {}, column {}", line, column)]
+    NoMatch {offset: usize, line: usize, column: usize}, 
+    /// Returned by Lexer::splice for lexers that can't insert tokens. 
+    #[fail(display = "this lexer doesn't support splice")]
+    SpliceUnsupported
+}
+
+/// # LowerError
+/// Returned by node::Lower implementations when a parse tree doesn't have 
+/// the shape the typed AST expects. It isn't generic over the token type, 
+/// so it can travel freely through downstream error types; offending nodes 
+/// and tokens are recorded by their Display form. 
+#[derive(Clone, Debug, Eq, Fail, Hash, Ord, PartialEq, PartialOrd)]
+pub enum LowerError {
+    /// A node (or its token) had no counterpart in the AST being built. 
+    #[fail(display = "cannot lower node: {}", node)]
+    UnexpectedNode {node: String}, 
+    /// A node had the wrong number of children for its token. 
+    #[fail(display = "{} expects {} children, found {}", token, expected, found)]
+    WrongArity {token: String, expected: usize, found: usize}, 
+    /// Anything else, such as a lexeme that fails to parse as a number. 
+    #[fail(display = "{}", message)]
+    Invalid {message: String}
+}
+
+impl LowerError {
+    ///LowerError::UnexpectedNode for `node`. 
+    pub fn unexpected<T: Token>(node: &Node<T>) -> LowerError {
+        LowerError::UnexpectedNode{node: node.to_string()}
+    }
 }
 
 #[cfg(test)]
@@ -83,4 +176,11 @@ mod test {
         fn assert_sync<T: Sync>() {}
         assert_sync::<ParseError<String>>();
     }
+
+    #[test]
+    fn test_lowererror_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LowerError>();
+        assert_send_sync::<LexError>();
+    }
 }
\ No newline at end of file
@@ -37,13 +37,36 @@
 //! Here is a simple wrapper around a vector as a reference/default
 //! implementation.
 //!
+//! StrLexer tokenizes source text with a user supplied closure and remembers 
+//! where each token came from, so errors can be reported as "line 3, column 14". 
+//! With the `regex` feature, LexerVec::from_regex does the same from a list 
+//! of regular expressions. 
+//!
+//! ## Panics
+//!
+//! The built-in lexers never index out of bounds: peek, try_next_token and 
+//! the DoubleEndedLexer methods return None at either end of the input. Only 
+//! next_token past the end panics, as the trait requires a token back; 
+//! check peek first, or use try_next_token. 
+//!
 
+use std::borrow::Cow;
 use std::fmt;
+use std::collections::VecDeque;
 use std::fmt::{Display, Formatter};
 use std::iter::FromIterator;
 
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+use errors::LexError;
 use token::Token;
 
+///A rule for LexerVec::from_regex: text matching the regex becomes the 
+/// token the function makes of it. 
+#[cfg(feature = "regex")]
+pub type RegexRule<T> = (Regex, fn(&str) -> T);
+
 ///Basic lexer trait that Parser implementations should use. 
 /// How one implements it is entirely up to implementors. 
 /// A basic implementation around a Vec is provided for convenience.
@@ -51,9 +74,129 @@ pub trait Lexer<T: Token> {
     ///Parser impls should use this before *every* next_token call. 
     fn peek(&self) -> Option<T>;
     ///Moves Lexer forward to the next token, returning it. 
+    /// Callers check peek first: implementations may panic at the end of 
+    /// the input. 
     fn next_token(&mut self) -> T;
-    //Moves Lexer backward to previous token, returning it.
-    fn prev_token(&mut self) -> T;
+    ///next_token, but None instead of a panic at the end of the input. 
+    fn try_next_token(&mut self) -> Option<T> {
+        self.peek()?;
+        Some(self.next_token())
+    }
+    ///Index of the next token, if the Lexer can tell. Parsers use it to 
+    /// check that loops make progress; lexers returning None opt out. 
+    fn position(&self) -> Option<usize> {
+        None
+    }
+    ///Borrows the next token instead of cloning it, for lexers that keep 
+    /// their tokens in memory. The default can't lend anything and returns 
+    /// None, so a None from peek_ref only means "use peek instead"; it doesn't 
+    /// mean the input is exhausted. 
+    fn peek_ref(&self) -> Option<&T> {
+        None
+    }
+    ///Where in the source the next token (the one peek returns) came from, 
+    /// for lexers that track it. 
+    fn current_span(&self) -> Option<Span> {
+        None
+    }
+    ///How many tokens are left, for progress reporting. None for lexers 
+    /// that can't tell, such as ones reading from a stream. 
+    fn remaining(&self) -> Option<usize> {
+        None
+    }
+    ///Inserts `tokens` at the current position, so they are the next ones 
+    /// peek and next_token return, e.g. to expand a macro invocation into its 
+    /// body. Anything that cached the result of peek (GeneralParser keeps a 
+    /// lookahead slot) has to drop it, since the next token changes; splice 
+    /// through Parser::splice rather than on the lexer directly. 
+    /// The built-in lexers all support it; the default returns 
+    /// LexError::SpliceUnsupported, for lexers that can't insert tokens. 
+    fn splice(&mut self, _tokens: Vec<T>) -> Result<(), LexError> {
+        Err(LexError::SpliceUnsupported)
+    }
+    ///The tokens not consumed yet, borrowed, for inspecting the rest of the 
+    /// input with the usual iterator adaptors without moving the lexer. Like 
+    /// peek_ref, only lexers that keep their tokens in memory can lend them; 
+    /// the default yields nothing. 
+    fn tokens(&self) -> Box<dyn Iterator<Item=&T> + '_> {
+        Box::new(::std::iter::empty())
+    }
+}
+
+//Lets a boxed lexer, including a Box<dyn Lexer<T>>, stand in wherever a 
+// Lexer is expected
+impl<T: Token, L: Lexer<T> + ?Sized> Lexer<T> for Box<L> {
+    fn peek(&self) -> Option<T> {
+        (**self).peek()
+    }
+
+    fn next_token(&mut self) -> T {
+        (**self).next_token()
+    }
+
+    fn try_next_token(&mut self) -> Option<T> {
+        (**self).try_next_token()
+    }
+
+    fn position(&self) -> Option<usize> {
+        (**self).position()
+    }
+
+    fn peek_ref(&self) -> Option<&T> {
+        (**self).peek_ref()
+    }
+
+    fn current_span(&self) -> Option<Span> {
+        (**self).current_span()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        (**self).remaining()
+    }
+
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), LexError> {
+        (**self).splice(tokens)
+    }
+
+    fn tokens(&self) -> Box<dyn Iterator<Item=&T> + '_> {
+        (**self).tokens()
+    }
+}
+
+///Location of a token in source text: the byte range it covers, plus the 
+/// 1-based line and column it starts at. StrLexer counts columns in chars 
+/// (Unicode scalar values) from the start of the line unless told otherwise 
+/// (see ColumnUnit). 
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Span {
+    pub start: usize, 
+    pub end: usize, 
+    pub line: usize, 
+    pub column: usize, 
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> ::arbitrary::Arbitrary<'a> for Span {
+    fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Span> {
+        Ok(Span{start: u.arbitrary()?, end: u.arbitrary()?, line: u.arbitrary()?, column: u.arbitrary()?})
+    }
+}
+
+///Lexers that can also move backward over tokens they've already handed out. 
+/// Backward movement is checked: stepping back past the first token gives 
+/// None rather than panicking. 
+pub trait DoubleEndedLexer<T: Token>: Lexer<T> {
+    ///Returns the most recently consumed token without moving the Lexer. 
+    fn peek_back(&self) -> Option<T>;
+    ///Moves Lexer backward by one token, returning it. The returned token 
+    /// is what peek/next_token will yield next. 
+    fn prev_token(&mut self) -> Option<T>;
 }
 
 /// Basic implementation of the Lexer trait
@@ -63,6 +206,9 @@ pub trait Lexer<T: Token> {
 pub struct LexerVec<T: Token> {
     inner: Vec<T>,
     index: usize,
+    //Span of each token, by index, when built with from_tokens_with_spans. 
+    // Tokens past the end (added by extend) have none. 
+    spans: Vec<Span>,
 }
 
 ///User facing view of LexerVec. 
@@ -83,7 +229,51 @@ impl<T: Token> LexerVec<T>
         let tokens = tokens.into_iter().map(|i|i.into()).collect();
         LexerVec {
             inner: tokens,
-            index: 0
+            index: 0,
+            spans: Vec::new()
+        }
+    }
+
+    ///Takes tokens along with where they came from, as produced by another 
+    /// lexer (logos, nom and the like), so a parser in spanned mode can 
+    /// report positions without re-lexing the source through StrLexer. 
+    pub fn from_tokens_with_spans(tokens: Vec<(T, Span)>) -> LexerVec<T> {
+        let (inner, spans) = tokens.into_iter().unzip();
+        LexerVec {
+            inner,
+            index: 0,
+            spans
+        }
+    }
+
+    ///Tokenizes `input` with `rules`, for prototyping: at each position 
+    /// (whitespace is skipped) the first rule whose regex matches there, 
+    /// non-empty, makes the token from the matched text. Tokens keep their 
+    /// spans, as with from_tokens_with_spans. 
+    /// 
+    /// Input that no rule matches is LexError::NoMatch. Regexes needn't be 
+    /// anchored with `^`, though anchoring keeps the search from scanning 
+    /// ahead for a match that would be discarded. 
+    #[cfg(feature = "regex")]
+    pub fn from_regex(input: &str, rules: &[RegexRule<T>]) -> Result<LexerVec<T>, LexError> {
+        let lexer = StrLexer::new(input, |rest| {
+            rules.iter().find_map(|(regex, make)| {
+                regex.find(rest)
+                    .filter(|found| found.start() == 0 && found.end() > 0)
+                    .map(|found| (make(found.as_str()), found.end()))
+            })
+        })?;
+        Ok(LexerVec::from_tokens_with_spans(lexer.inner))
+    }
+
+    ///A LexerSlice over the same tokens, at the same position, for handing 
+    /// the rest of the input to code that takes a borrowed lexer without 
+    /// cloning the buffer. Positions agree with this lexer's, and the slice 
+    /// can still step back over tokens already consumed here. 
+    pub fn as_slice_lexer(&self) -> LexerSlice<'_, T> {
+        LexerSlice {
+            inner: Cow::Borrowed(&self.inner),
+            index: self.index
         }
     }
 
@@ -95,39 +285,83 @@ impl<T: Token> LexerVec<T>
         <Self as Lexer<T>>::next_token(self)
     }
 
-    fn prev_token(&mut self) -> T {
-        <Self as Lexer<T>>::prev_token(self)
+    fn peek_back(&self) -> Option<T> {
+        <Self as DoubleEndedLexer<T>>::peek_back(self)
+    }
+
+    fn prev_token(&mut self) -> Option<T> {
+        <Self as DoubleEndedLexer<T>>::prev_token(self)
     }
 }
 
 impl<T: Token> Lexer<T> for LexerVec<T>
 {
-    ///Basic index bounds checking. 
-    /// index is usize, so can never be less 
-    /// than 0. 
     fn peek(&self) -> Option<T> {
-        if self.index < self.inner.len() {
-            Some(self.inner[self.index].clone())
-        } else {
-            None
-        }
+        self.inner.get(self.index).cloned()
     }
 
-    ///Returns token pointed to by current index, then increments it
-    /// (with bounds checking)
+    ///Returns token pointed to by current index, then increments it. 
+    /// 
+    /// # Panics
+    /// Panics if there are no tokens left. 
     fn next_token(&mut self) -> T {
-        let t = self.inner[self.index].clone();
+        self.try_next_token().expect("LexerVec::next_token called with no tokens left")
+    }
+
+    fn try_next_token(&mut self) -> Option<T> {
+        let t = self.inner.get(self.index).cloned()?;
         self.index += 1;
-        t
+        Some(t)
+    }
+
+    fn position(&self) -> Option<usize> {
+        Some(self.index)
     }
 
-    ///Returns token pointed to by the current index, then decrements it
-    /// There is implied bounds checking in that usize can never be 
-    /// less than 0. 
-    fn prev_token(&mut self) -> T {
-        let t = self.inner[self.index].clone();
-        self.index -= 1;
-        t
+    fn peek_ref(&self) -> Option<&T> {
+        self.inner.get(self.index)
+    }
+
+    fn current_span(&self) -> Option<Span> {
+        self.spans.get(self.index).cloned()
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.inner.len().saturating_sub(self.index))
+    }
+
+    ///Spliced tokens take the span of the token they are inserted before, 
+    /// like the expansion of a macro reports the invocation's position. 
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), LexError> {
+        let index = self.index;
+        if let Some(&span) = self.spans.get(index).or_else(|| self.spans.last()) {
+            if index <= self.spans.len() {
+                self.spans.splice(index..index, tokens.iter().map(|_| span));
+            }
+        }
+        self.inner.splice(index..index, tokens);
+        Ok(())
+    }
+
+    fn tokens(&self) -> Box<dyn Iterator<Item=&T> + '_> {
+        Box::new(self.inner.get(self.index..).unwrap_or(&[]).iter())
+    }
+}
+
+impl<T: Token> DoubleEndedLexer<T> for LexerVec<T>
+{
+    ///The token just before the current index, if any. 
+    fn peek_back(&self) -> Option<T> {
+        self.inner.get(self.index.checked_sub(1)?).cloned()
+    }
+
+    ///Decrements the index (never below 0), then returns the token 
+    /// it now points to. 
+    fn prev_token(&mut self) -> Option<T> {
+        let index = self.index.checked_sub(1)?;
+        let t = self.inner.get(index).cloned()?;
+        self.index = index;
+        Some(t)
     }
 }
 
@@ -138,15 +372,501 @@ impl<T: Token, I: Into<T>> FromIterator<I> for LexerVec<T> {
     }
 }
 
+///Yields the tokens not consumed yet. 
+impl<T: Token> IntoIterator for LexerVec<T> {
+    type Item = T;
+    type IntoIter = ::std::vec::IntoIter<T>;
+
+    ///Spans, if any, are dropped. 
+    fn into_iter(mut self) -> ::std::vec::IntoIter<T> {
+        self.inner.drain(..self.index);
+        self.inner.into_iter()
+    }
+}
+
 impl<T: Token> Extend<T> for LexerVec<T> {
     fn extend<I: IntoIterator<Item=T>>(&mut self, iter: I) {
         self.inner.extend(iter);
     }
 }
 
+/// Lexer over a borrowed slice of tokens. 
+/// 
+/// Like LexerVec, but for tokens that already live in a buffer: nothing is 
+/// allocated or copied up front. Tokens are still cloned one at a time by 
+/// peek and next_token, because the Lexer trait hands out owned tokens. 
+/// Returning `&T` from the trait would avoid those clones, but only lexers 
+/// that keep their tokens around (like this one) could implement it; 
+/// streaming lexers that build each token on demand have nothing to lend. 
+/// Splicing tokens in is the exception: it copies the slice into a Vec the 
+/// first time. 
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct LexerSlice<'a, T: Token + 'a> {
+    inner: Cow<'a, [T]>,
+    index: usize,
+}
+
+impl<'a, T: Token> Display for LexerSlice<'a, T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "(LexerSlice)")
+    }
+}
+
+#[allow(dead_code)]
+impl<'a, T: Token> LexerSlice<'a, T> {
+    pub fn new(tokens: &'a [T]) -> LexerSlice<'a, T> {
+        LexerSlice {
+            inner: Cow::Borrowed(tokens),
+            index: 0
+        }
+    }
+
+    fn peek(&self) -> Option<T> {
+        <Self as Lexer<T>>::peek(self)
+    }
+
+    fn next_token(&mut self) -> T {
+        <Self as Lexer<T>>::next_token(self)
+    }
+
+    fn peek_back(&self) -> Option<T> {
+        <Self as DoubleEndedLexer<T>>::peek_back(self)
+    }
+
+    fn prev_token(&mut self) -> Option<T> {
+        <Self as DoubleEndedLexer<T>>::prev_token(self)
+    }
+}
+
+impl<'a, T: Token> Lexer<T> for LexerSlice<'a, T> {
+    fn peek(&self) -> Option<T> {
+        self.inner.get(self.index).cloned()
+    }
+
+    ///# Panics
+    /// Panics if there are no tokens left. 
+    fn next_token(&mut self) -> T {
+        self.try_next_token().expect("LexerSlice::next_token called with no tokens left")
+    }
+
+    fn try_next_token(&mut self) -> Option<T> {
+        let t = self.inner.get(self.index).cloned()?;
+        self.index += 1;
+        Some(t)
+    }
+
+    fn position(&self) -> Option<usize> {
+        Some(self.index)
+    }
+
+    fn peek_ref(&self) -> Option<&T> {
+        self.inner.get(self.index)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.inner.len().saturating_sub(self.index))
+    }
+
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), LexError> {
+        let index = self.index;
+        self.inner.to_mut().splice(index..index, tokens);
+        Ok(())
+    }
+
+    fn tokens(&self) -> Box<dyn Iterator<Item=&T> + '_> {
+        Box::new(self.inner.get(self.index..).unwrap_or(&[]).iter())
+    }
+}
+
+impl<'a, T: Token> DoubleEndedLexer<T> for LexerSlice<'a, T> {
+    fn peek_back(&self) -> Option<T> {
+        self.inner.get(self.index.checked_sub(1)?).cloned()
+    }
+
+    fn prev_token(&mut self) -> Option<T> {
+        let index = self.index.checked_sub(1)?;
+        let t = self.inner.get(index).cloned()?;
+        self.index = index;
+        Some(t)
+    }
+}
+
+impl<'a, T: Token> From<&'a [T]> for LexerSlice<'a, T> {
+    fn from(tokens: &'a [T]) -> LexerSlice<'a, T> {
+        LexerSlice::new(tokens)
+    }
+}
+
+/// What StrLexer counts columns in. 
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ColumnUnit {
+    /// Unicode scalar values: a token after ```é``` or an emoji is one 
+    /// column further on, as an editor showing the line would have it 
+    /// (bar grapheme clusters made of several chars, and wide characters). 
+    #[default]
+    Chars, 
+    /// UTF-8 bytes, for tools that index lines by byte offset. 
+    Bytes, 
+}
+
+/// Lexer over source text that records a Span for every token. 
+/// 
+/// The text is tokenized up front by a closure, which is handed the remaining 
+/// input (with leading whitespace already skipped) and returns the next token 
+/// along with its length in bytes, or None if nothing there is a token. 
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct StrLexer<T: Token> {
+    inner: Vec<(T, Span)>,
+    index: usize,
+}
+
+impl<T: Token> Display for StrLexer<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "(StrLexer)")
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Token> StrLexer<T> {
+    ///Tokenizes `src` with `tokenize`. Columns count chars. 
+    /// 
+    /// Input that `tokenize` returns None for is LexError::NoMatch, at the 
+    /// offset it starts at, rather than a lexer over the tokens before it. So 
+    /// is a zero length, or one that runs past the input or doesn't end on a 
+    /// char boundary. 
+    pub fn new<F>(src: &str, tokenize: F) -> Result<StrLexer<T>, LexError> 
+        where F: FnMut(&str) -> Option<(T, usize)>
+    {
+        StrLexer::with_columns(src, ColumnUnit::default(), tokenize)
+    }
+
+    ///Like new, with columns counted in `unit` (NoMatch's column too). 
+    pub fn with_columns<F>(src: &str, unit: ColumnUnit, mut tokenize: F) -> Result<StrLexer<T>, LexError> 
+        where F: FnMut(&str) -> Option<(T, usize)>
+    {
+        let mut inner = Vec::new();
+        let (mut offset, mut line, mut column) = (0, 1, 1);
+        {
+            //Moves past `len` bytes, keeping line and column in step
+            let mut skip = |offset: &mut usize, len: usize| {
+                for c in src[*offset..*offset + len].chars() {
+                    if c == '\n' {
+                        line += 1;
+                        column = 1;
+                    } else {
+                        column += match unit {
+                            ColumnUnit::Chars => 1, 
+                            ColumnUnit::Bytes => c.len_utf8()
+                        };
+                    }
+                }
+                *offset += len;
+                (line, column)
+            };
+            loop {
+                let rest = &src[offset..];
+                let (l, c) = skip(&mut offset, rest.len() - rest.trim_start().len());
+                if offset == src.len() {
+                    break
+                }
+                match tokenize(&src[offset..]) {
+                    Some((token, len)) if len > 0 && src.is_char_boundary(offset + len) => {
+                        let span = Span{start: offset, end: offset + len, line: l, column: c};
+                        skip(&mut offset, len);
+                        inner.push((token, span));
+                    }, 
+                    _ => return Err(LexError::NoMatch{offset, line: l, column: c})
+                }
+            }
+        }
+        Ok(StrLexer {
+            inner,
+            index: 0
+        })
+    }
+
+    fn peek(&self) -> Option<T> {
+        <Self as Lexer<T>>::peek(self)
+    }
+
+    fn next_token(&mut self) -> T {
+        <Self as Lexer<T>>::next_token(self)
+    }
+
+    fn current_span(&self) -> Option<Span> {
+        <Self as Lexer<T>>::current_span(self)
+    }
+}
+
+impl<T: Token> Lexer<T> for StrLexer<T> {
+    fn peek(&self) -> Option<T> {
+        self.inner.get(self.index).map(|(token, _)| token.clone())
+    }
+
+    ///# Panics
+    /// Panics if there are no tokens left. 
+    fn next_token(&mut self) -> T {
+        self.try_next_token().expect("StrLexer::next_token called with no tokens left")
+    }
+
+    fn try_next_token(&mut self) -> Option<T> {
+        let t = self.inner.get(self.index)?.0.clone();
+        self.index += 1;
+        Some(t)
+    }
+
+    fn position(&self) -> Option<usize> {
+        Some(self.index)
+    }
+
+    fn peek_ref(&self) -> Option<&T> {
+        self.inner.get(self.index).map(|(token, _)| token)
+    }
+
+    fn current_span(&self) -> Option<Span> {
+        self.inner.get(self.index).map(|&(_, span)| span)
+    }
+
+    fn remaining(&self) -> Option<usize> {
+        Some(self.inner.len().saturating_sub(self.index))
+    }
+
+    ///Spliced tokens take the span of the token they are inserted before, as 
+    /// with LexerVec (the last token's at the end of the input). 
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), LexError> {
+        let index = self.index;
+        let span = self.inner.get(index).or_else(|| self.inner.last())
+            .map_or(Span{start: 0, end: 0, line: 1, column: 1}, |&(_, span)| span);
+        self.inner.splice(index..index, tokens.into_iter().map(|token| (token, span)));
+        Ok(())
+    }
+
+    fn tokens(&self) -> Box<dyn Iterator<Item=&T> + '_> {
+        Box::new(self.inner.get(self.index..).unwrap_or(&[]).iter().map(|(token, _)| token))
+    }
+}
+
+impl<T: Token> DoubleEndedLexer<T> for StrLexer<T> {
+    fn peek_back(&self) -> Option<T> {
+        self.inner.get(self.index.checked_sub(1)?).map(|(token, _)| token.clone())
+    }
+
+    fn prev_token(&mut self) -> Option<T> {
+        let index = self.index.checked_sub(1)?;
+        let t = self.inner.get(index)?.0.clone();
+        self.index = index;
+        Some(t)
+    }
+}
+
+type LineColumn<L> = Box<dyn Fn(&L) -> Option<(usize, usize)> + Send + Sync>;
+
+/// Adapter for offside-rule grammars (Python, Haskell and the like): wraps 
+/// another lexer and inserts newline, indent and dedent tokens wherever the 
+/// layout of the source calls for them. They are ordinary tokens of type T, 
+/// so the grammar registers rules for them like any other. 
+/// 
+/// Layout is worked out from a function returning the (line, column) of the 
+/// next token of the wrapped lexer. For a StrLexer that is 
+/// `|lexer| lexer.current_span().map(|span| (span.line, span.column))`. 
+/// 
+/// The first token sets the base indentation. Each token that starts a new 
+/// line is preceded by a newline token, followed by an indent if the line is 
+/// indented further than the enclosing block, or by a dedent for every block 
+/// it closes. At the end of input the last line gets its newline, and every 
+/// block still open is closed. 
+pub struct LayoutLexer<T: Token, L: Lexer<T>> {
+    inner: L,
+    line_column: LineColumn<L>,
+    newline: T,
+    indent: T,
+    dedent: T,
+    //Synthesized tokens waiting to be handed out before the next inner token
+    pending: VecDeque<T>,
+    //Columns of the open blocks, innermost last
+    levels: Vec<usize>,
+    line: Option<usize>,
+    finished: bool,
+    count: usize,
+}
+
+impl<T: Token, L: Lexer<T>> Display for LayoutLexer<T, L> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "(LayoutLexer)")
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Token, L: Lexer<T>> LayoutLexer<T, L> {
+    pub fn new<F>(inner: L, newline: T, indent: T, dedent: T, line_column: F) -> LayoutLexer<T, L> 
+        where F: Fn(&L) -> Option<(usize, usize)> + Send + Sync + 'static
+    {
+        let mut lexer = LayoutLexer {
+            inner,
+            line_column: Box::new(line_column),
+            newline,
+            indent,
+            dedent,
+            pending: VecDeque::new(),
+            levels: Vec::new(),
+            line: None,
+            finished: false,
+            count: 0,
+        };
+        lexer.layout();
+        lexer
+    }
+
+    ///Gives back the wrapped lexer. 
+    pub fn into_inner(self) -> L {
+        self.inner
+    }
+
+    fn peek(&self) -> Option<T> {
+        <Self as Lexer<T>>::peek(self)
+    }
+
+    fn next_token(&mut self) -> T {
+        <Self as Lexer<T>>::next_token(self)
+    }
+
+    //Queues the tokens that go between the last inner token and the next one. 
+    // Runs whenever the inner lexer moves, since peek can't mutate. 
+    fn layout(&mut self) {
+        if self.finished {
+            return;
+        }
+        if self.inner.peek().is_none() {
+            self.finished = true;
+            if self.line.is_some() {
+                self.pending.push_back(self.newline.clone());
+            }
+            for _ in 1..self.levels.len() {
+                self.pending.push_back(self.dedent.clone());
+            }
+            return;
+        }
+        let (line, column) = match (self.line_column)(&self.inner) {
+            Some(position) => position, 
+            None => return
+        };
+        match self.line {
+            None => self.levels.push(column), 
+            Some(last) if line > last => {
+                self.pending.push_back(self.newline.clone());
+                let current = *self.levels.last().unwrap_or(&column);
+                if column > current {
+                    self.levels.push(column);
+                    self.pending.push_back(self.indent.clone());
+                } else {
+                    //The base level is never closed
+                    while self.levels.len() > 1 && self.levels.last().is_some_and(|&level| level > column) {
+                        self.levels.pop();
+                        self.pending.push_back(self.dedent.clone());
+                    }
+                }
+            }, 
+            Some(_) => {}
+        }
+        self.line = Some(line);
+    }
+}
+
+impl<T: Token, L: Lexer<T>> Lexer<T> for LayoutLexer<T, L> {
+    fn peek(&self) -> Option<T> {
+        match self.pending.front() {
+            Some(token) => Some(token.clone()), 
+            None => self.inner.peek()
+        }
+    }
+
+    fn next_token(&mut self) -> T {
+        self.count += 1;
+        match self.pending.pop_front() {
+            Some(token) => token, 
+            None => {
+                let token = self.inner.next_token();
+                self.layout();
+                token
+            }
+        }
+    }
+
+    //Counts synthesized tokens too, so it moves on every next_token
+    fn position(&self) -> Option<usize> {
+        Some(self.count)
+    }
+
+    fn peek_ref(&self) -> Option<&T> {
+        match self.pending.front() {
+            Some(token) => Some(token), 
+            None => self.inner.peek_ref()
+        }
+    }
+
+    //Synthesized tokens report the span of the token they precede
+    fn current_span(&self) -> Option<Span> {
+        self.inner.current_span()
+    }
+
+    ///Spliced tokens come out as they are, ahead of any synthesized ones 
+    /// still waiting: they take no part in the layout. 
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), LexError> {
+        for token in tokens.into_iter().rev() {
+            self.pending.push_front(token);
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+
+    #[test]
+    fn test_ends_of_input_are_checked() {
+        let mut lexer: LexerVec<String> = LexerVec::new(Vec::<String>::new());
+        assert_eq!(lexer.try_next_token(), None);
+        assert_eq!(lexer.prev_token(), None);
+        assert_eq!(lexer.peek_back(), None);
+        assert_eq!(lexer.remaining(), Some(0));
+        assert_eq!(lexer.tokens().count(), 0);
+
+        let tokens = ["a".to_string()];
+        let mut lexer = LexerSlice::new(&tokens);
+        assert_eq!(lexer.try_next_token(), Some("a".to_string()));
+        assert_eq!(lexer.try_next_token(), None);
+        assert_eq!(lexer.prev_token(), Some("a".to_string()));
+        assert_eq!(lexer.prev_token(), None);
+
+        //A length past the end of the input is no match
+        assert_eq!(StrLexer::new("ab", |rest| Some((rest.to_string(), rest.len() + 1))), 
+            Err(LexError::NoMatch{offset: 0, line: 1, column: 1}));
+    }
+
+    #[test]
+    fn test_strlexer_reports_unlexable_input() {
+        //Digits only; the % in the middle isn't dropped along with what follows
+        let digits = |rest: &str| {
+            let len = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+            if len == 0 { None } else { Some((rest[..len].to_string(), len)) }
+        };
+        assert_eq!(StrLexer::new("12 3\n 4 % 5", digits), 
+            Err(LexError::NoMatch{offset: 8, line: 2, column: 4}));
+        assert_eq!(StrLexer::with_columns("é % 5", ColumnUnit::Bytes, |rest: &str| {
+            let c = rest.chars().next()?;
+            if c == '%' { None } else { Some((c.to_string(), c.len_utf8())) }
+        }), Err(LexError::NoMatch{offset: 3, line: 1, column: 4}));
+        assert_eq!(StrLexer::new("12 3", digits).map(|lexer| lexer.inner.len()), Ok(2));
+    }
+
+    #[test]
+    #[should_panic(expected = "no tokens left")]
+    fn test_next_token_past_end_panics_with_message() {
+        let mut lexer: LexerVec<String> = LexerVec::new(vec!["a".to_string()]);
+        lexer.next_token();
+        lexer.next_token();
+    }
     //Catch Send/Sync changes
     #[test]
     fn test_lexervec_send() {
@@ -159,4 +879,329 @@ mod test {
         fn assert_sync<T: Sync>() {}
         assert_sync::<LexerVec<String>>();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_forward_backward_interleaving() {
+        let mut lexer = LexerVec::new(vec!["a", "b", "c"].into_iter().map(String::from));
+        assert_eq!(lexer.peek_back(), None);
+        assert_eq!(lexer.prev_token(), None);
+
+        assert_eq!(lexer.peek_ref(), Some(&"a".to_string()));
+        assert_eq!(lexer.next_token(), "a");
+        assert_eq!(lexer.next_token(), "b");
+        assert_eq!(lexer.peek_back(), Some("b".to_string()));
+        assert_eq!(lexer.prev_token(), Some("b".to_string()));
+        assert_eq!(lexer.peek(), Some("b".to_string()));
+        assert_eq!(lexer.peek_back(), Some("a".to_string()));
+
+        assert_eq!(lexer.next_token(), "b");
+        assert_eq!(lexer.next_token(), "c");
+        assert_eq!(lexer.peek(), None);
+        //Stepping back from the end is fine too
+        assert_eq!(lexer.prev_token(), Some("c".to_string()));
+        assert_eq!(lexer.prev_token(), Some("b".to_string()));
+        assert_eq!(lexer.prev_token(), Some("a".to_string()));
+        assert_eq!(lexer.prev_token(), None);
+        assert_eq!(lexer.peek(), Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_tokens_after_partial_consumption() {
+        let mut lexer: LexerVec<String> = LexerVec::new(vec!["a", "+", "b", "*", "c"].into_iter().map(String::from));
+        lexer.next_token();
+        lexer.next_token();
+        let idents: Vec<&String> = lexer.tokens().filter(|tk| tk.chars().all(char::is_alphabetic)).collect();
+        assert_eq!(idents, vec!["b", "c"]);
+        //Looking didn't move the lexer
+        assert_eq!(lexer.peek_ref(), Some(&"b".to_string()));
+
+        let boxed: Box<dyn Lexer<String>> = Box::new(lexer.clone());
+        assert_eq!(boxed.tokens().count(), 3);
+        assert_eq!(lexer.into_iter().collect::<Vec<_>>(), vec!["b", "*", "c"]);
+
+        let mut lexer = words("let x = 1");
+        lexer.next_token();
+        assert_eq!(lexer.tokens().take_while(|tk| *tk != "=").cloned().collect::<Vec<_>>(), vec!["x"]);
+    }
+
+    #[test]
+    fn test_remaining_counts_down() {
+        let mut lexer: LexerVec<String> = LexerVec::new(vec!["a", "b", "c"].into_iter().map(String::from));
+        assert_eq!(lexer.remaining(), Some(3));
+        lexer.next_token();
+        assert_eq!(lexer.remaining(), Some(2));
+        lexer.next_token();
+        lexer.next_token();
+        assert_eq!(lexer.remaining(), Some(0));
+        lexer.prev_token();
+        assert_eq!(lexer.remaining(), Some(1));
+
+        let mut lexer = words("let x = 1");
+        assert_eq!(lexer.remaining(), Some(4));
+        lexer.next_token();
+        assert_eq!(lexer.remaining(), Some(3));
+
+        let mut lexer = LexerSlice::new(&[1, 2]);
+        lexer.next_token();
+        assert_eq!(lexer.remaining(), Some(1));
+    }
+
+    #[test]
+    fn test_lexervec_splice_at_index() {
+        let mut lexer = LexerVec::new(vec!["a", "m", "b"].into_iter().map(String::from));
+        lexer.next_token();
+        lexer.next_token();
+        lexer.splice(vec!["x".to_string(), "y".to_string()]).unwrap();
+        assert_eq!(lexer.peek(), Some("x".to_string()));
+        assert_eq!(lexer.next_token(), "x");
+        assert_eq!(lexer.next_token(), "y");
+        assert_eq!(lexer.next_token(), "b");
+        //The spliced tokens are part of the history from then on
+        assert_eq!(lexer.prev_token(), Some("b".to_string()));
+        assert_eq!(lexer.prev_token(), Some("y".to_string()));
+    }
+
+    #[test]
+    fn test_splice_on_every_builtin_lexer() {
+        let drain = |lexer: &mut dyn Lexer<String>| {
+            let mut tokens = Vec::new();
+            while let Some(token) = lexer.try_next_token() {
+                tokens.push(token);
+            }
+            tokens
+        };
+        let tokens: Vec<String> = vec!["a".to_string(), "b".to_string()];
+        let mut slice = LexerSlice::new(&tokens);
+        slice.next_token();
+        slice.splice(vec!["x".to_string()]).unwrap();
+        assert_eq!(drain(&mut slice), vec!["x", "b"]);
+        //The borrowed tokens are untouched
+        assert_eq!(tokens, vec!["a", "b"]);
+
+        let mut text = words("a b");
+        text.next_token();
+        text.splice(vec!["x".to_string()]).unwrap();
+        assert_eq!(text.current_span(), Some(span(2, 3, 1, 3)));
+        assert_eq!(drain(&mut text), vec!["x", "b"]);
+
+        let mut layout = LayoutLexer::new(words("a\n  b"), "NL".to_string(), "INDENT".to_string(), "DEDENT".to_string(), 
+            |lexer: &StrLexer<String>| lexer.current_span().map(|span| (span.line, span.column)));
+        layout.next_token();
+        layout.splice(vec!["x".to_string()]).unwrap();
+        assert_eq!(drain(&mut layout), vec!["x", "NL", "INDENT", "b", "NL", "DEDENT"]);
+    }
+
+    #[test]
+    fn test_splice_unsupported_by_default() {
+        struct Once(Option<char>);
+        impl Lexer<char> for Once {
+            fn peek(&self) -> Option<char> {
+                self.0
+            }
+
+            fn next_token(&mut self) -> char {
+                self.0.take().unwrap()
+            }
+        }
+        assert_eq!(Once(Some('a')).splice(vec!['b']), Err(LexError::SpliceUnsupported));
+    }
+
+    #[test]
+    fn test_lexervec_from_tokens_with_spans() {
+        let span = |start, end| Span{start, end, line: 1, column: start + 1};
+        let mut lexer = LexerVec::from_tokens_with_spans(vec![
+            ("a".to_string(), span(0, 1)), 
+            ("+".to_string(), span(2, 3)), 
+            ("bc".to_string(), span(4, 6)), 
+        ]);
+        assert_eq!(lexer.current_span(), Some(span(0, 1)));
+        assert_eq!(lexer.next_token(), "a");
+        assert_eq!(lexer.current_span(), Some(span(2, 3)));
+        lexer.splice(vec!["(".to_string()]).unwrap();
+        assert_eq!(lexer.current_span(), Some(span(2, 3)));
+        lexer.next_token();
+        lexer.next_token();
+        assert_eq!(lexer.current_span(), Some(span(4, 6)));
+        lexer.next_token();
+        assert_eq!(lexer.current_span(), None);
+
+        //Plain LexerVecs have no spans
+        assert_eq!(LexerVec::<String>::new(vec!["a".to_string()]).current_span(), None);
+    }
+
+    #[test]
+    fn test_lexervec_as_slice_lexer() {
+        let mut lexer = LexerVec::new(vec!['a', 'b', 'c', 'd']);
+        lexer.next_token();
+        lexer.next_token();
+        let mut slice = lexer.as_slice_lexer();
+        assert_eq!(slice.position(), lexer.position());
+        assert_eq!(slice.tokens().collect::<Vec<_>>(), vec![&'c', &'d']);
+        assert_eq!(slice.next_token(), 'c');
+        assert_eq!(slice.next_token(), 'd');
+        assert_eq!(slice.peek(), None);
+        assert_eq!(slice.prev_token(), Some('d'));
+
+        //The slice's progress is its own
+        assert_eq!(lexer.peek(), Some('c'));
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn test_lexervec_from_regex() {
+        let rules: Vec<RegexRule<String>> = vec![
+            (Regex::new(r"^[0-9]+").unwrap(), |text| format!("num:{}", text)), 
+            (Regex::new(r"^[-+*/()]").unwrap(), str::to_string), 
+        ];
+        let mut lexer = LexerVec::from_regex("12 + 3*(4 - 5)", &rules).unwrap();
+        assert_eq!(lexer.tokens().cloned().collect::<Vec<_>>(), vec![
+            "num:12", "+", "num:3", "*", "(", "num:4", "-", "num:5", ")"
+        ]);
+        assert_eq!(lexer.current_span(), Some(Span{start: 0, end: 2, line: 1, column: 1}));
+        lexer.next_token();
+        assert_eq!(lexer.current_span(), Some(Span{start: 3, end: 4, line: 1, column: 4}));
+
+        assert!(LexerVec::from_regex("  ", &rules).unwrap().peek().is_none());
+        assert_eq!(LexerVec::from_regex("1 +\n 2 % 3", &rules).map(|_| ()), 
+            Err(LexError::NoMatch{offset: 7, line: 2, column: 4}));
+    }
+
+    #[test]
+    fn test_lexerslice_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LexerSlice<'static, String>>();
+    }
+
+    #[test]
+    fn test_lexerslice_from_slice_literal() {
+        let mut lexer = LexerSlice::new(&['a', 'b', 'c']);
+        assert_eq!(lexer.peek(), Some('a'));
+        assert_eq!(lexer.next_token(), 'a');
+        assert_eq!(lexer.next_token(), 'b');
+        assert_eq!(lexer.position(), Some(2));
+        assert_eq!(lexer.prev_token(), Some('b'));
+        assert_eq!(lexer.peek_back(), Some('a'));
+        assert_eq!(lexer.next_token(), 'b');
+        assert_eq!(lexer.next_token(), 'c');
+        assert_eq!(lexer.peek(), None);
+
+        let tokens = ["x".to_string(), "y".to_string()];
+        let mut lexer = LexerSlice::from(&tokens[1..]);
+        assert_eq!(lexer.next_token(), "y");
+        assert_eq!(lexer.peek(), None);
+    }
+
+    //Words are runs of alphanumerics, anything else is a single char token
+    fn words(src: &str) -> StrLexer<String> {
+        StrLexer::new(src, |rest| {
+            let len = match rest.find(|c: char| !c.is_alphanumeric()) {
+                Some(0) => rest.chars().next().unwrap().len_utf8(), 
+                Some(len) => len, 
+                None => rest.len()
+            };
+            Some((rest[..len].to_string(), len))
+        }).unwrap()
+    }
+
+    fn span(start: usize, end: usize, line: usize, column: usize) -> Span {
+        Span{start, end, line, column}
+    }
+
+    #[test]
+    fn test_str_lexer_spans() {
+        let mut lexer = words("let x =\n  foo + 1;\n\nbar");
+        let mut spans = Vec::new();
+        while let Some(span) = lexer.current_span() {
+            spans.push((lexer.next_token(), span));
+        }
+        assert_eq!(spans, vec![
+            ("let".to_string(), span(0, 3, 1, 1)), 
+            ("x".to_string(), span(4, 5, 1, 5)), 
+            ("=".to_string(), span(6, 7, 1, 7)), 
+            ("foo".to_string(), span(10, 13, 2, 3)), 
+            ("+".to_string(), span(14, 15, 2, 7)), 
+            ("1".to_string(), span(16, 17, 2, 9)), 
+            (";".to_string(), span(17, 18, 2, 10)), 
+            ("bar".to_string(), span(20, 23, 4, 1)), 
+        ]);
+        assert_eq!(lexer.peek(), None);
+        assert_eq!(lexer.current_span(), None);
+    }
+
+    #[test]
+    fn test_layout_lexer_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LayoutLexer<String, StrLexer<String>>>();
+    }
+
+    #[test]
+    fn test_layout_lexer_indented_block() {
+        let src = "if a:\n    b\n    while c:\n        d\n    e\nf\n";
+        let mut lexer = LayoutLexer::new(words(src), "NL".to_string(), "INDENT".to_string(), "DEDENT".to_string(), 
+            |lexer: &StrLexer<String>| lexer.current_span().map(|span| (span.line, span.column)));
+        let mut tokens = Vec::new();
+        while lexer.peek().is_some() {
+            tokens.push(lexer.next_token());
+        }
+        assert_eq!(tokens, vec![
+            "if", "a", ":", "NL", 
+            "INDENT", "b", "NL", 
+            "while", "c", ":", "NL", 
+            "INDENT", "d", "NL", 
+            "DEDENT", "e", "NL", 
+            "DEDENT", "f", "NL", 
+        ]);
+        assert_eq!(lexer.position(), Some(tokens.len()));
+    }
+
+    #[test]
+    fn test_layout_lexer_closes_blocks_at_end() {
+        let mut lexer = LayoutLexer::new(words("a\n  b\n    c"), "NL".to_string(), "INDENT".to_string(), "DEDENT".to_string(), 
+            |lexer: &StrLexer<String>| lexer.current_span().map(|span| (span.line, span.column)));
+        let mut tokens = Vec::new();
+        while lexer.peek().is_some() {
+            tokens.push(lexer.next_token());
+        }
+        assert_eq!(tokens, vec!["a", "NL", "INDENT", "b", "NL", "INDENT", "c", "NL", "DEDENT", "DEDENT"]);
+
+        let lexer = LayoutLexer::new(words(""), "NL".to_string(), "INDENT".to_string(), "DEDENT".to_string(), 
+            |lexer: &StrLexer<String>| lexer.current_span().map(|span| (span.line, span.column)));
+        assert_eq!(lexer.peek(), None);
+    }
+
+    #[test]
+    fn test_str_lexer_column_resets_after_newline() {
+        let mut lexer = words("abc\nd\r\n  e");
+        assert_eq!(lexer.current_span().map(|s| s.to_string()), Some("1:1".to_string()));
+        lexer.next_token();
+        assert_eq!(lexer.current_span(), Some(span(4, 5, 2, 1)));
+        lexer.next_token();
+        assert_eq!(lexer.current_span(), Some(span(9, 10, 3, 3)));
+        assert_eq!(lexer.prev_token(), Some("d".to_string()));
+        assert_eq!(lexer.current_span(), Some(span(4, 5, 2, 1)));
+    }
+
+    #[test]
+    fn test_str_lexer_multibyte_columns() {
+        let src = "é 😀 x\n  ü y";
+        let mut lexer = words(src);
+        let mut columns = Vec::new();
+        while let Some(span) = lexer.current_span() {
+            columns.push((lexer.next_token(), span.line, span.column));
+        }
+        assert_eq!(columns, vec![
+            ("é".to_string(), 1, 1), 
+            ("😀".to_string(), 1, 3), 
+            ("x".to_string(), 1, 5), 
+            ("ü".to_string(), 2, 3), 
+            ("y".to_string(), 2, 5), 
+        ]);
+
+        let lexer: StrLexer<String> = StrLexer::with_columns(src, ColumnUnit::Bytes, |rest| {
+            let len = rest.chars().next()?.len_utf8();
+            Some((rest[..len].to_string(), len))
+        }).unwrap();
+        //x follows 2 + 1 + 4 + 1 bytes
+        assert_eq!(lexer.inner[2].1, span(8, 9, 1, 9));
+    }
+}
@@ -98,7 +98,7 @@
 //!         |parser, token, lbp, node| {
 //!             Ok(Node::Composite{token: token.clone(), children: vec![
 //!                 node, 
-//!                 parser.parse_expr(lbp)?]})
+//!                 parser.parse_expr(lbp)?], span: None})
 //!     });
 //!     spec.add_left_assoc(
 //!         CToken::Mul, 
@@ -106,7 +106,7 @@
 //!         |parser, token, lbp, node| {
 //!             Ok(Node::Composite{token: token.clone(), children: vec![
 //!                 node, 
-//!                 parser.parse_expr(lbp)?]})
+//!                 parser.parse_expr(lbp)?], span: None})
 //!     });
 //! 
 //!     let lexer = LexerVec::new(vec![
@@ -138,6 +138,45 @@
 //! examples/token_spec.rs shows an example of how to implement the traits for 
 //! the token type so it can be used to lookup the parse rules (uses HashMap).
 //! 
+//! > cargo run --example kinded_token_spec
+//! 
+//! examples/kinded_token_spec.rs is the same grammar using token::KindedToken, 
+//! which keeps the token kind and the matched text apart.
+//! 
+//! > cargo run --example lower_ast
+//! 
+//! examples/lower_ast.rs turns the parse tree into a typed AST with node::Lower.
+//! 
+//! > cargo run --example named_levels
+//! 
+//! examples/named_levels.rs registers its operators at semantic levels such as 
+//! PrecedenceLevel::ADDITIVE instead of numbered ones. 
+//! 
+//! ## Logging
+//! 
+//! With the `logging` feature, GeneralParser reports rule dispatch, binding 
+//! power comparisons and consumed tokens through `log::trace!` under the 
+//! "prattle" target. Without it the calls compile away entirely. 
+//! 
+//! ## Parallel parsing
+//! 
+//! With the `rayon` feature, parse_many_parallel parses many independent 
+//! token vectors at once, one spec clone per input. 
+//! 
+//! ## Fuzzing
+//! 
+//! With the `arbitrary` feature, Node and Span implement arbitrary::Arbitrary, 
+//! and arbitrary_tokens turns fuzzer bytes into a token stream for any token 
+//! type that implements it. fuzz/ holds a cargo-fuzz target that feeds such 
+//! streams to an arithmetic grammar: 
+//! > cargo fuzz run parse
+//! 
+//! ## Regex lexing
+//! 
+//! With the `regex` feature, LexerVec::from_regex tokenizes text with a list 
+//! of (Regex, constructor) rules, for prototyping a grammar before writing 
+//! a lexer for it. 
+//! 
 //! ## Citations
 //! > [1] Vaughan R. Pratt. 1973. Top down operator precedence. In Proceedings
 //! > of the 1st annual ACM SIGACT-SIGPLAN symposium on Principles of 
@@ -145,10 +184,33 @@
 //! > DOI=http://dx.doi.org/10.1145/512927.512931
 //! 
 
+//failure_derive predates the non_local_definitions lint
+#![allow(non_local_definitions)]
+
 #[macro_use] extern crate failure;
+#[cfg(feature = "logging")]
+#[macro_use] extern crate log;
+#[cfg(feature = "rayon")]
+extern crate rayon;
+#[cfg(feature = "arbitrary")]
+extern crate arbitrary;
+#[cfg(feature = "regex")]
+extern crate regex;
+
+//log::trace! with the logging feature, nothing at all without it
+#[cfg(feature = "logging")]
+macro_rules! log_trace {
+    ($($arg:tt)*) => { trace!(target: "prattle", $($arg)*) };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! log_trace {
+    ($($arg:tt)*) => {};
+}
 
 #[macro_use] pub mod macros;
 
+pub mod convenience;
 pub mod errors;
 pub mod lexer;
 pub mod node;
@@ -157,23 +219,44 @@ pub mod precedence;
 pub mod spec;
 pub mod token;
 
+pub use convenience::parse_str;
+#[cfg(feature = "rayon")]
+pub use convenience::parse_many_parallel;
+#[cfg(feature = "arbitrary")]
+pub use convenience::arbitrary_tokens;
+
 /// Handy prelude mod containing everything you need to get started. 
 pub mod prelude {
-    pub use errors::ParseError;
-    pub use lexer::{Lexer, LexerVec};
-    pub use node::Node;
-    pub use parser::{Parser, GeneralParser};
-    pub use precedence::PrecedenceLevel;
-    pub use spec::{ParserSpec, SpecificationError};
+    pub use errors::{LexError, LowerError, ParseError};
+    pub use lexer::{ColumnUnit, DoubleEndedLexer, LayoutLexer, Lexer, LexerSlice, LexerVec, Span, StrLexer};
+    pub use node::{Lower, Node, Transformer};
+    pub use parser::{BacktrackingParser, Parser, GeneralParser, ParseEvent, TokenRole, TraceEvent};
+    pub use precedence::{Associativity, PrecedenceLevel};
+    pub use spec::{FrozenSpec, ParserSpec, SpecificationError};
     pub use token::Token;
 }
 
 //Little container mod for type aliases that are convenient and short
 pub mod types {
+    use std::collections::HashMap;
+    use std::collections::hash_map::RandomState;
+    use std::sync::Arc;
+
     use super::prelude::*;
-    pub type NullDenotation<T> = fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>>;
-    pub type LeftDenotation<T> = fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>>;
+    use super::token::{DiscriminantKey, KeyFn};
+    //Arc rather than Box so that a spec holding stateful closures stays Clone, 
+    // and cloning it only bumps reference counts. 
+    pub type NullDenotation<T> = Arc<dyn Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync>;
+    pub type LeftDenotation<T> = Arc<dyn Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync>;
 
-    pub type NullInfo<T> = (PrecedenceLevel, NullDenotation<T>);
+    //(bp, rbp, rule): the rule is handed rbp, for parsing its operand
+    pub type NullInfo<T> = (PrecedenceLevel, PrecedenceLevel, NullDenotation<T>);
     pub type LeftInfo<T> = (PrecedenceLevel, PrecedenceLevel, LeftDenotation<T>);
+
+    //A left rule picked by the shape of its left operand, for 
+    // ParserSpec::add_left_assoc_matching
+    pub type NodeMatcher<T> = (fn(&Node<T>) -> bool, LeftDenotation<T>);
+
+    pub type NullMap<T, K = DiscriminantKey, S = RandomState> = HashMap<<K as KeyFn<T>>::Key, NullInfo<T>, S>;
+    pub type LeftMap<T, K = DiscriminantKey, S = RandomState> = HashMap<<K as KeyFn<T>>::Key, LeftInfo<T>, S>;
 }
\ No newline at end of file
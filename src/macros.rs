@@ -22,14 +22,56 @@
 
 //! # Utility Macros
 //! 
-//! Three macros are provided:
-//!     add_null_assoc
-//!     add_left_assoc
-//!     add_left_right_assoc
+//! Nine macros are provided:
+//!     add_null_assoc, try_add_null_assoc
+//!     add_left_assoc, try_add_left_assoc
+//!     add_left_right_assoc, try_add_left_right_assoc
+//!     precedence_table, try_precedence_table
+//!     assert_parses_to
 //!     
-//! These macros allow for the assignment of multiple tokens in one go, presented as
+//! The add_ macros allow for the assignment of multiple tokens in one go, presented as
 //! an alternative to the ParserSpec.add_multi_null_assoc, etc methods
 //! 
+//! precedence_table builds on them to register a whole operator table at once. 
+//! 
+//! assert_parses_to is for grammar tests: it parses a token list with a spec 
+//! and compares the tree against the expected one. 
+//! 
+//! ## Which form to use
+//! The plain forms apply `?` to every registration, so they can only be used 
+//! inside a function returning `Result<_, SpecificationError<T>>` (or an error 
+//! type SpecificationError converts into). 
+//! 
+//! The `try_` forms (try_add_null_assoc, try_add_left_assoc, 
+//! try_add_left_right_assoc, try_precedence_table) instead evaluate to a 
+//! `Result<(), SpecificationError<T>>`, stopping at the first failed 
+//! registration. Use them in `main`, tests, builders or anywhere else `?` 
+//! isn't available, and handle the Result as you see fit:
+//! 
+//! ```rust
+//! # #[macro_use] extern crate prattle;
+//! # use prattle::prelude::*;
+//! # use std::fmt;
+//! #[derive(Clone, Debug, PartialEq)]
+//! enum Tk { Num(u32), Add, Sub }
+//! # impl fmt::Display for Tk {
+//! #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+//! # }
+//! 
+//! # fn main() {
+//! let mut spec = ParserSpec::new();
+//! try_add_null_assoc!(spec, PrecedenceLevel::Root, (Tk::Num(0)) => |_, tk, _| Ok(Node::Simple(tk)))
+//!     .expect("Num registered twice");
+//! try_add_left_assoc!(spec, PrecedenceLevel::First, (Tk::Add, Tk::Sub) => |parser, tk, lbp, node| {
+//!     Ok(Node::branch(tk, vec![node, parser.parse_expr(lbp)?]))
+//! }).expect("Add/Sub registered twice");
+//! 
+//! let tokens = vec![Tk::Num(1), Tk::Sub, Tk::Num(2)];
+//! let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+//! assert_eq!(parser.parse(), Ok(Node::branch(Tk::Sub, vec![Node::Simple(Tk::Num(1)), Node::Simple(Tk::Num(2))])));
+//! # }
+//! ```
+//! 
 
 //Utility macros to assign same left_binding_power/right_binding_power values and closures for tokens
 
@@ -58,4 +100,299 @@ macro_rules! add_left_right_assoc {
             $spec.add_left_right_assoc($token, $lbp, $rbp, $clsr)?;
         )*
     };
+}
+
+/// Registers a whole table of infix operators in one block. 
+/// 
+/// Each row is `(tokens), level, associativity => closure;`, and the lbp/rbp for 
+/// the row are computed from the associativity (see Associativity::binding_powers). 
+/// Like the other macros, errors are propagated with `?`. 
+/// 
+/// ```rust
+/// # #[macro_use] extern crate prattle;
+/// # use prattle::prelude::*;
+/// # use std::fmt;
+/// #[derive(Clone, Debug, PartialEq)]
+/// enum Tk { Num(u32), Add, Sub, Mul, Pow }
+/// # impl fmt::Display for Tk {
+/// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+/// # }
+/// 
+/// fn spec() -> Result<ParserSpec<Tk>, SpecificationError<Tk>> {
+///     let mut spec = ParserSpec::new();
+///     spec.add_null_assoc(Tk::Num(0), PrecedenceLevel::Root, |_, tk, _| Ok(Node::Simple(tk)))?;
+///     precedence_table!(spec, {
+///         (Tk::Add, Tk::Sub), PrecedenceLevel::First, Associativity::Left => |parser, tk, lbp, node| {
+///             Ok(Node::Composite{token: tk, children: vec![node, parser.parse_expr(lbp)?], span: None})
+///         };
+///         (Tk::Mul), PrecedenceLevel::Second, Associativity::Left => |parser, tk, lbp, node| {
+///             Ok(Node::Composite{token: tk, children: vec![node, parser.parse_expr(lbp)?], span: None})
+///         };
+///         (Tk::Pow), PrecedenceLevel::Third, Associativity::Right => |parser, tk, lbp, node| {
+///             Ok(Node::Composite{token: tk, children: vec![node, parser.parse_expr(lbp)?], span: None})
+///         };
+///     });
+///     Ok(spec)
+/// }
+/// 
+/// # fn main() {
+/// let tokens = vec![Tk::Num(2), Tk::Pow, Tk::Num(3), Tk::Pow, Tk::Num(4)];
+/// let mut parser = GeneralParser::new(spec().unwrap(), LexerVec::new(tokens));
+/// // Pow is right associative: 2 ^ (3 ^ 4)
+/// assert_eq!(parser.parse(), Ok(Node::Composite{token: Tk::Pow, children: vec![
+///     Node::Simple(Tk::Num(2)), 
+///     Node::Composite{token: Tk::Pow, children: vec![Node::Simple(Tk::Num(3)), Node::Simple(Tk::Num(4))], span: None}
+/// ], span: None}));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! precedence_table {
+    ($spec:ident, { $( ($($token:expr),* $(,)*), $level:expr, $assoc:expr => $clsr:expr );* $(;)* }) => {
+        $({
+            let (lbp, rbp) = $crate::precedence::Associativity::binding_powers($assoc, $level);
+            $(
+                $spec.add_left_right_assoc($token, lbp, rbp, $clsr)?;
+            )*
+        })*
+    };
+}
+
+#[macro_export]
+macro_rules! try_add_null_assoc {
+    ($spec:ident, $lbp:expr, ($($token:expr),* $(,)*) => $clsr:expr) => {{
+        let mut result = Ok(());
+        $(
+            if result.is_ok() {
+                result = $spec.add_null_assoc($token, $lbp, $clsr);
+            }
+        )*
+        result
+    }};
+}
+
+#[macro_export]
+macro_rules! try_add_left_assoc {
+    ($spec:ident, $lbp:expr, ($($token:expr),* $(,)*) => $clsr:expr) => {{
+        let mut result = Ok(());
+        $(
+            if result.is_ok() {
+                result = $spec.add_left_assoc($token, $lbp, $clsr);
+            }
+        )*
+        result
+    }};
+}
+
+#[macro_export]
+macro_rules! try_add_left_right_assoc {
+    ($spec:ident, $lbp:expr, $rbp:expr, ($($token:expr),* $(,)*) => $clsr:expr) => {{
+        let mut result = Ok(());
+        $(
+            if result.is_ok() {
+                result = $spec.add_left_right_assoc($token, $lbp, $rbp, $clsr);
+            }
+        )*
+        result
+    }};
+}
+
+#[macro_export]
+macro_rules! try_precedence_table {
+    ($spec:ident, { $( ($($token:expr),* $(,)*), $level:expr, $assoc:expr => $clsr:expr );* $(;)* }) => {{
+        let mut result = Ok(());
+        $({
+            let (lbp, rbp) = $crate::precedence::Associativity::binding_powers($assoc, $level);
+            $(
+                if result.is_ok() {
+                    result = $spec.add_left_right_assoc($token, lbp, rbp, $clsr);
+                }
+            )*
+        })*
+        result
+    }};
+}
+
+/// Parses `tokens` with a GeneralParser over `spec` and asserts the result is 
+/// `expected`. On a mismatch it panics with both trees pretty-printed, 
+/// differing lines marked `-` (expected) and `+` (found). 
+/// 
+/// ```rust
+/// # #[macro_use] extern crate prattle;
+/// # use prattle::prelude::*;
+/// # use std::fmt;
+/// # #[derive(Clone, Debug, PartialEq)]
+/// # enum Tk { Num(u32), Add }
+/// # impl fmt::Display for Tk {
+/// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+/// # }
+/// # fn arith_spec() -> ParserSpec<Tk> {
+/// #     let mut spec = ParserSpec::new();
+/// #     spec.add_null_assoc(Tk::Num(0), PrecedenceLevel::Root, |_, tk, _| Ok(Node::Simple(tk))).unwrap();
+/// #     spec.add_left_assoc(Tk::Add, PrecedenceLevel::First, |parser, tk, lbp, node| {
+/// #         Ok(Node::branch(tk, vec![node, parser.parse_expr(lbp)?]))
+/// #     }).unwrap();
+/// #     spec
+/// # }
+/// # fn main() {
+/// assert_parses_to!(arith_spec(), vec![Tk::Num(1), Tk::Add, Tk::Num(2)], 
+///     Node::Composite{token: Tk::Add, children: vec![Node::Simple(Tk::Num(1)), Node::Simple(Tk::Num(2))], span: None});
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_parses_to {
+    ($spec:expr, $tokens:expr, $expected:expr) => {{
+        let expected = $expected;
+        let mut parser = $crate::parser::GeneralParser::new($spec, $crate::lexer::LexerVec::new($tokens));
+        match $crate::parser::Parser::parse(&mut parser) {
+            Ok(ref found) if *found == expected => {}, 
+            Ok(found) => panic!("parse tree mismatch (- expected, + found):\n{}", $crate::node::pretty_diff(&expected, &found)), 
+            Err(err) => panic!("parse failed: {}\nexpected:\n{}", err, expected.pretty())
+        }
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt;
+
+    use prelude::*;
+
+    //The plain forms need a function returning Result to compile in
+    fn plain_spec() -> Result<ParserSpec<Tk>, SpecificationError<Tk>> {
+        let mut spec = ParserSpec::new();
+        add_null_assoc!(spec, PrecedenceLevel::Root, (Tk::Num(0)) => |_, tk, _| Ok(Node::Simple(tk)));
+        add_left_assoc!(spec, PrecedenceLevel::First, (Tk::Add) => |parser, tk, lbp, node| {
+            Ok(Node::branch(tk, vec![node, parser.parse_expr(lbp)?]))
+        });
+        add_left_right_assoc!(spec, PrecedenceLevel::Second, PrecedenceLevel::Second, (Tk::Mul) => |parser, tk, lbp, node| {
+            Ok(Node::branch(tk, vec![node, parser.parse_expr(lbp)?]))
+        });
+        precedence_table!(spec, {
+            (Tk::Pow), PrecedenceLevel::Third, Associativity::Right => |parser, tk, lbp, node| {
+                Ok(Node::branch(tk, vec![node, parser.parse_expr(lbp)?]))
+            };
+        });
+        Ok(spec)
+    }
+
+    #[test]
+    fn test_plain_forms() {
+        let binary = |tk, lhs, rhs| Node::branch(tk, vec![lhs, rhs]);
+        assert_parses_to!(plain_spec().unwrap(), 
+            vec![Tk::Num(1), Tk::Add, Tk::Num(2), Tk::Mul, Tk::Num(3), Tk::Pow, Tk::Num(4), Tk::Pow, Tk::Num(5)], 
+            binary(Tk::Add, num(1), binary(Tk::Mul, num(2), binary(Tk::Pow, num(3), binary(Tk::Pow, num(4), num(5))))));
+
+        //Errors propagate out through the ?
+        let twice = || -> Result<(), SpecificationError<Tk>> {
+            let mut spec = plain_spec()?;
+            add_left_assoc!(spec, PrecedenceLevel::First, (Tk::Add) => |_, _, _, node| Ok(node));
+            Ok(())
+        };
+        match twice() {
+            Err(SpecificationError::TokenToRuleAlreadyDefined{tk}) => assert_eq!(tk, Tk::Add), 
+            other => panic!("expected TokenToRuleAlreadyDefined, got {:?}", other)
+        }
+    }
+
+    //The try_ forms work without a Result to return
+    #[test]
+    fn test_try_add_null_assoc() {
+        let mut spec: ParserSpec<String> = ParserSpec::new();
+        let res = try_add_null_assoc!(spec, PrecedenceLevel::Root, ("a".to_string()) => |_, tk, _| {
+            Ok(Node::Simple(tk))
+        });
+        assert!(res.is_ok());
+        //Second registration of the same variant fails, and stops there
+        let res = try_add_null_assoc!(spec, PrecedenceLevel::Root, ("b".to_string(), "c".to_string()) => |_, tk, _| {
+            Ok(Node::Simple(tk))
+        });
+        match res {
+            Err(SpecificationError::TokenToRuleAlreadyDefined{tk}) => assert_eq!(tk, "b"), 
+            other => panic!("expected TokenToRuleAlreadyDefined, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_try_add_left_assoc() {
+        let mut spec: ParserSpec<String> = ParserSpec::new();
+        let res = try_add_left_assoc!(spec, PrecedenceLevel::First, ("+".to_string()) => |_, _, _, node| {
+            Ok(node)
+        });
+        assert!(res.is_ok());
+        let res = try_add_left_assoc!(spec, PrecedenceLevel::First, ("-".to_string()) => |_, _, _, node| {
+            Ok(node)
+        });
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn test_try_add_left_right_assoc() {
+        let mut spec: ParserSpec<String> = ParserSpec::new();
+        let res = try_add_left_right_assoc!(spec, PrecedenceLevel::First, PrecedenceLevel::Second, ("^".to_string()) => |_, _, _, node| {
+            Ok(node)
+        });
+        assert!(res.is_ok());
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Tk { Num(u32), Add, Mul, Pow }
+
+    impl fmt::Display for Tk {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    fn arith_spec() -> ParserSpec<Tk> {
+        let mut spec = ParserSpec::new();
+        try_add_null_assoc!(spec, PrecedenceLevel::Root, (Tk::Num(0)) => |_, tk, _| Ok(Node::Simple(tk))).unwrap();
+        try_precedence_table!(spec, {
+            (Tk::Add), PrecedenceLevel::First, Associativity::Left => |parser, tk, lbp, node| {
+                Ok(Node::Composite{token: tk, children: vec![node, parser.parse_expr(lbp)?], span: None})
+            };
+            (Tk::Mul), PrecedenceLevel::Second, Associativity::Left => |parser, tk, lbp, node| {
+                Ok(Node::Composite{token: tk, children: vec![node, parser.parse_expr(lbp)?], span: None})
+            };
+        }).unwrap();
+        spec
+    }
+
+    fn num(n: u32) -> Node<Tk> {
+        Node::Simple(Tk::Num(n))
+    }
+
+    #[test]
+    fn test_assert_parses_to() {
+        assert_parses_to!(arith_spec(), vec![Tk::Num(1), Tk::Add, Tk::Num(2), Tk::Mul, Tk::Num(3)], 
+            Node::Composite{token: Tk::Add, children: vec![
+                num(1), 
+                Node::Composite{token: Tk::Mul, children: vec![num(2), num(3)], span: None}], span: None});
+        assert_parses_to!(arith_spec(), vec![Tk::Num(7)], num(7));
+    }
+
+    #[test]
+    #[should_panic(expected = "-   Num(2)\n+   Num(3)")]
+    fn test_assert_parses_to_mismatch() {
+        assert_parses_to!(arith_spec(), vec![Tk::Num(1), Tk::Add, Tk::Num(3)], 
+            Node::Composite{token: Tk::Add, children: vec![num(1), num(2)], span: None});
+    }
+
+    #[test]
+    #[should_panic(expected = "parse failed: missing a Null syntax rule for: Add")]
+    fn test_assert_parses_to_error() {
+        assert_parses_to!(arith_spec(), vec![Tk::Add], num(1));
+    }
+
+    #[test]
+    fn test_try_precedence_table() {
+        let mut spec: ParserSpec<String> = ParserSpec::new();
+        let res = try_precedence_table!(spec, {
+            ("^".to_string()), PrecedenceLevel::Third, Associativity::Right => |_, _, _, node| Ok(node);
+        });
+        assert!(res.is_ok());
+        let res = try_precedence_table!(spec, {
+            ("*".to_string()), PrecedenceLevel::Second, Associativity::Left => |_, _, _, node| Ok(node);
+        });
+        assert!(res.is_err());
+    }
 }
\ No newline at end of file
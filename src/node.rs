@@ -34,16 +34,458 @@
 //! 
 // TODO: Make this a trait and let users manage ASTNode definition and construction? 
 
+use std::cmp::min;
 use std::fmt::{Display, Error, Formatter};
+use std::hash::BuildHasher;
+use std::mem::discriminant;
 
-use token::Token;
+#[cfg(feature = "arbitrary")]
+use arbitrary::{self, Arbitrary, Unstructured};
 
+use errors::LowerError;
+use lexer::Span;
+use precedence::PrecedenceLevel;
+use spec::ParserSpec;
+use token::{KeyFn, Token};
+
+///A parse tree over tokens of type T. 
+/// 
+/// Only Token's bounds are required of T. The derived Eq, Hash, PartialOrd 
+/// and Ord impls apply when T has them too, so nodes can go in sets and 
+/// map keys, but tokens that can't be ordered or hashed (ones carrying an 
+/// f64, say) still make perfectly good trees. 
+/// 
+/// Dropping, cloning, comparing and printing a tree all recurse once per 
+/// level of nesting, so a tree has to stay shallow enough for the stack: a 
+/// left associative chain of n operators is n levels deep even though the 
+/// parser builds it in a loop. Tens of thousands of levels can overflow the 
+/// default 8 MiB main thread stack; parse longer chains on a thread with a 
+/// bigger stack, or with a flattening rule such as ParserSpec::add_flat_infix. 
 #[derive(Debug, Clone, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Node<T: Token> {
     Simple(T), 
+    ///A node with children. `span` is the source range the node was parsed 
+    /// from, recorded by a parser in spanned mode (see 
+    /// GeneralParser::set_spanned) and None otherwise. 
     Composite {
         token: T,
-        children: Vec<Node<T>>
+        children: Vec<Node<T>>, 
+        span: Option<Span>
+    }, 
+    ///Placeholder left by a parser in error recovery mode where a subexpression 
+    /// couldn't be parsed. `recovered_at` is the token parsing resumed from, 
+    /// if there was one. 
+    Error {
+        message: String, 
+        recovered_at: Option<T>
+    }
+}
+
+///Alternative name for Node, for grammars written against the older 
+/// SimpleNode naming. 
+pub type SimpleNode<T> = Node<T>;
+
+/// Constructors, shorter than spelling out the variants in every rule. 
+impl<T: Token> Node<T> {
+    ///A Simple node holding `token`. 
+    pub fn leaf(token: T) -> Node<T> {
+        Node::Simple(token)
+    }
+
+    ///A Composite node with `token` at the root. 
+    pub fn branch(token: T, children: Vec<Node<T>>) -> Node<T> {
+        Node::Composite{token, children, span: None}
+    }
+}
+
+///A Simple node, so a rule can end with ```Ok(token.into())```. 
+impl<T: Token> From<T> for Node<T> {
+    fn from(token: T) -> Node<T> {
+        Node::Simple(token)
+    }
+}
+
+///A Composite node, so a rule can end with ```Ok((token, vec![lhs, rhs]).into())```. 
+impl<T: Token> From<(T, Vec<Node<T>>)> for Node<T> {
+    fn from((token, children): (T, Vec<Node<T>>)) -> Node<T> {
+        Node::Composite{token, children, span: None}
+    }
+}
+
+/// Accessors, so tree-walking code doesn't have to pattern match on every node. 
+impl<T: Token> Node<T> {
+    ///The node's token: the leaf token of a Simple node, or the root token 
+    /// of a Composite node. Error nodes have no token. 
+    pub fn token(&self) -> Option<&T> {
+        match *self {
+            Node::Simple(ref token) => Some(token), 
+            Node::Composite{ref token, ..} => Some(token), 
+            Node::Error{..} => None
+        }
+    }
+
+    ///Whether this is an Error node left by error recovery. 
+    pub fn is_error(&self) -> bool {
+        matches!(*self, Node::Error{..})
+    }
+
+    ///The node's children, an empty slice for a Simple or Error node. 
+    pub fn children(&self) -> &[Node<T>] {
+        match *self {
+            Node::Composite{ref children, ..} => children, 
+            _ => &[]
+        }
+    }
+
+    ///Consumes the node, returning its children (empty for a Simple or Error node). 
+    pub fn into_children(self) -> Vec<Node<T>> {
+        match self {
+            Node::Composite{children, ..} => children, 
+            _ => Vec::new()
+        }
+    }
+
+    ///The source range of a Composite node parsed in spanned mode. 
+    pub fn span(&self) -> Option<Span> {
+        match *self {
+            Node::Composite{span, ..} => span, 
+            _ => None
+        }
+    }
+
+    ///Clears the span of every node in the tree. 
+    pub fn strip_spans(self) -> Node<T> {
+        match self {
+            Node::Composite{token, children, ..} => Node::Composite{
+                token, 
+                children: children.into_iter().map(Node::strip_spans).collect(), 
+                span: None
+            }, 
+            node => node
+        }
+    }
+
+    ///Appends a child node. 
+    /// 
+    /// A Simple node is converted in place into a Composite node with the same 
+    /// token and `child` as its only child, so a leaf can grow into a subtree. 
+    /// 
+    /// # Panics
+    /// Panics on an Error node, which has no token to become a Composite root. 
+    pub fn push_child(&mut self, child: Node<T>) {
+        if let Node::Simple(ref token) = *self {
+            *self = Node::Composite{token: token.clone(), children: Vec::new(), span: None};
+        }
+        match *self {
+            Node::Composite{ref mut children, ..} => children.push(child), 
+            _ => panic!("Node::push_child called on an Error node")
+        }
+    }
+
+    ///Replaces the child at `idx`, returning the old child. 
+    /// 
+    /// Returns None (and leaves the node untouched) if `idx` is out of range, 
+    /// which is always the case for a Simple or Error node. 
+    pub fn replace_child(&mut self, idx: usize, child: Node<T>) -> Option<Node<T>> {
+        match *self {
+            Node::Composite{ref mut children, ..} if idx < children.len() => {
+                Some(::std::mem::replace(&mut children[idx], child))
+            }, 
+            _ => None
+        }
+    }
+
+    ///Depth-first search for the first node whose token matches `pred`, 
+    /// checking a node before its children. 
+    pub fn find<F: Fn(&T) -> bool>(&self, pred: F) -> Option<&Node<T>> {
+        self.find_with(&pred)
+    }
+
+    ///Every node whose token matches `pred`, in document order (a node 
+    /// before its children, children left to right). 
+    pub fn find_all<F: Fn(&T) -> bool>(&self, pred: F) -> Vec<&Node<T>> {
+        let mut found = Vec::new();
+        self.find_all_with(&pred, &mut found);
+        found
+    }
+
+    ///Compares shape and token kinds only: tokens are compared by their enum 
+    /// discriminant, so `Ident("a")` and `Ident("b")` match, and children are 
+    /// compared recursively. Handy for golden tests that don't care about lexemes. 
+    /// Error nodes match any other Error node, whatever their message, and 
+    /// spans are ignored. 
+    pub fn structural_eq(&self, other: &Node<T>) -> bool {
+        match (self, other) {
+            (Node::Simple(a), Node::Simple(b)) => discriminant(a) == discriminant(b), 
+            (Node::Composite{token: a, children: ac, ..}, 
+             Node::Composite{token: b, children: bc, ..}) => {
+                discriminant(a) == discriminant(b) 
+                    && ac.len() == bc.len() 
+                    && ac.iter().zip(bc).all(|(x, y)| x.structural_eq(y))
+            }, 
+            (Node::Error{..}, Node::Error{..}) => true, 
+            _ => false
+        }
+    }
+
+    ///Flattens the tree into reverse Polish notation: each node's children, 
+    /// left to right, followed by its own token. 
+    /// 
+    /// Arity is not recorded in the output; a stack machine consuming it has to 
+    /// know how many operands each token pops (two for the usual binary operators, 
+    /// none for a Simple node's token). Error nodes contribute nothing. 
+    pub fn to_postfix(&self) -> Vec<T> {
+        let mut out = Vec::new();
+        self.postfix_into(&mut out);
+        out
+    }
+
+    ///The tokens of the Simple nodes in the tree, left to right: the 
+    /// operands of an expression, without its operators. Error nodes 
+    /// contribute nothing. 
+    pub fn leaves(&self) -> Vec<&T> {
+        let mut out = Vec::new();
+        self.leaves_into(&mut out);
+        out
+    }
+
+    ///Renders the tree one node per line, children indented under their 
+    /// parent, for reading trees in test failures and debug output. 
+    /// 
+    /// ```text
+    /// Add
+    ///   Ident(a)
+    ///   Mul
+    ///     Ident(b)
+    ///     Ident(c)
+    /// ```
+    pub fn pretty(&self) -> String {
+        let mut out = String::new();
+        self.pretty_into(0, &mut out);
+        out
+    }
+
+    ///Renders the tree as an S-expression, compact and easy to diff in golden 
+    /// tests: a leaf is its token's Display, a Composite node is 
+    /// ```(token child1 child2 ...)```, so ```a + b * c``` comes out as 
+    /// ```(+ a (* b c))```. Spans are left out; an Error node is 
+    /// ```(error "message")```. 
+    pub fn to_sexpr(&self) -> String {
+        let mut out = String::new();
+        self.sexpr_into(&mut out);
+        out
+    }
+
+    fn sexpr_into(&self, out: &mut String) {
+        match *self {
+            Node::Simple(ref token) => out.push_str(&token.to_string()), 
+            Node::Composite{ref token, ref children, ..} => {
+                out.push('(');
+                out.push_str(&token.to_string());
+                for child in children {
+                    out.push(' ');
+                    child.sexpr_into(out);
+                }
+                out.push(')');
+            }, 
+            Node::Error{ref message, ..} => out.push_str(&format!("(error {:?})", message))
+        }
+    }
+
+    fn pretty_into(&self, depth: usize, out: &mut String) {
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+        if let Some(span) = self.span() {
+            out.push_str(&format!("@{} ", span));
+        }
+        match *self {
+            Node::Simple(ref token) | Node::Composite{ref token, ..} => out.push_str(&token.to_string()), 
+            Node::Error{ref message, ..} => out.push_str(&format!("Error({})", message))
+        }
+        out.push('\n');
+        for child in self.children() {
+            child.pretty_into(depth + 1, out);
+        }
+    }
+
+    fn postfix_into(&self, out: &mut Vec<T>) {
+        for child in self.children() {
+            child.postfix_into(out);
+        }
+        if let Some(token) = self.token() {
+            out.push(token.clone());
+        }
+    }
+
+    fn leaves_into<'a>(&'a self, out: &mut Vec<&'a T>) {
+        match *self {
+            Node::Simple(ref token) => out.push(token), 
+            _ => for child in self.children() {
+                child.leaves_into(out);
+            }
+        }
+    }
+
+    fn find_with<F: Fn(&T) -> bool>(&self, pred: &F) -> Option<&Node<T>> {
+        if self.token().is_some_and(pred) {
+            return Some(self);
+        }
+        self.children().iter().filter_map(|child| child.find_with(pred)).next()
+    }
+
+    fn find_all_with<'a, F: Fn(&T) -> bool>(&'a self, pred: &F, found: &mut Vec<&'a Node<T>>) {
+        if self.token().is_some_and(pred) {
+            found.push(self);
+        }
+        for child in self.children() {
+            child.find_all_with(pred, found);
+        }
+    }
+}
+
+///Line by line comparison of the pretty forms of two trees: lines that 
+/// differ are shown as `- expected` / `+ found` pairs. Used by 
+/// assert_parses_to!. 
+#[doc(hidden)]
+pub fn pretty_diff<T: Token>(expected: &Node<T>, found: &Node<T>) -> String {
+    let (expected, found) = (expected.pretty(), found.pretty());
+    let (mut expected, mut found) = (expected.lines(), found.lines());
+    let mut out = String::new();
+    loop {
+        match (expected.next(), found.next()) {
+            (None, None) => break, 
+            (Some(e), Some(f)) if e == f => out.push_str(&format!("  {}\n", e)), 
+            (e, f) => {
+                if let Some(e) = e {
+                    out.push_str(&format!("- {}\n", e));
+                }
+                if let Some(f) = f {
+                    out.push_str(&format!("+ {}\n", f));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Printing a tree back as source text. 
+impl<T: Token + Send + Sync + 'static> Node<T> {
+    ///Renders the tree as infix source, with as few parentheses as `spec`'s 
+    /// binding powers allow: ```a + (b * c)``` parses and prints back as 
+    /// ```a + b * c```, while ```(a + b) * c``` keeps them, as does 
+    /// ```a - (b - c)``` for a left associative `-`. 
+    /// 
+    /// A Composite node with two children and a left rule is an infix 
+    /// operator, one with a single child and a null rule a prefix operator. 
+    /// Other Composite nodes (calls, say) come out as ```token(child, ...)```, 
+    /// leaves and Error nodes as in to_sexpr. Grouping is always written 
+    /// with ```(``` and ```)```, whatever the grammar's brackets are. 
+    pub fn to_source<K: KeyFn<T>, S: BuildHasher>(&self, spec: &ParserSpec<T, K, S>) -> String {
+        self.source_with(spec).0
+    }
+
+    //The source, and the lowest operand level along its right edge: an 
+    // operator that follows it and binds above that level would be taken 
+    // into the last operand, so it has to be parenthesized first. 
+    fn source_with<K: KeyFn<T>, S: BuildHasher>(&self, spec: &ParserSpec<T, K, S>) -> (String, Option<PrecedenceLevel>) {
+        let (token, children) = match *self {
+            Node::Composite{ref token, ref children, ..} => (token, children), 
+            ref node => return (node.to_sexpr(), None)
+        };
+        match (children.len(), spec.left_bp(token), spec.null_rule(token)) {
+            (2, Some((operand, bp)), _) => {
+                let (lhs, lhs_tail) = children[0].source_with(spec);
+                let lhs = if lhs_tail.is_some_and(|tail| tail < bp) { format!("({})", lhs) } else { lhs };
+                let (rhs, tail) = children[1].operand_source(spec, operand);
+                (format!("{} {} {}", lhs, token, rhs), Some(tail.map_or(operand, |tail| min(tail, operand))))
+            }, 
+            (1, _, Some(&(_, rbp, _))) => {
+                let (operand, tail) = children[0].operand_source(spec, rbp);
+                let prefix = token.to_string();
+                let glued = prefix.ends_with(char::is_alphanumeric) && operand.starts_with(char::is_alphanumeric);
+                (format!("{}{}{}", prefix, if glued { " " } else { "" }, operand), Some(tail.map_or(rbp, |tail| min(tail, rbp))))
+            }, 
+            _ => {
+                let args: Vec<String> = children.iter().map(|child| child.to_source(spec)).collect();
+                (format!("{}({})", token, args.join(", ")), None)
+            }
+        }
+    }
+
+    //source_with for the operand an operator parses at `level`: an infix 
+    // operator that wouldn't bind there is parenthesized. 
+    fn operand_source<K: KeyFn<T>, S: BuildHasher>(&self, spec: &ParserSpec<T, K, S>, level: PrecedenceLevel) -> (String, Option<PrecedenceLevel>) {
+        let (source, tail) = self.source_with(spec);
+        let infix_bp = match *self {
+            Node::Composite{ref token, ref children, ..} if children.len() == 2 => spec.left_bp(token).map(|bps| bps.1), 
+            _ => None
+        };
+        if infix_bp.is_some_and(|bp| bp <= level) {
+            (format!("({})", source), None)
+        } else {
+            (source, tail)
+        }
+    }
+}
+
+/// Conversion from a parse tree into a typed AST. 
+/// 
+/// Implement it for your AST type, usually by matching on the node's token and 
+/// lowering the children recursively, then call Node::lower_into. Shape errors 
+/// are reported as LowerError so every lowering pass fails the same way. 
+pub trait Lower<T: Token>: Sized {
+    fn lower(node: Node<T>) -> Result<Self, LowerError>;
+}
+
+impl<T: Token> Node<T> {
+    ///Lowers this tree into `A`; the same as `A::lower(node)`, but reads 
+    /// better at the end of a parse. 
+    pub fn lower_into<A: Lower<T>>(self) -> Result<A, LowerError> {
+        A::lower(self)
+    }
+}
+
+/// A rewriting pass over a parse tree, such as constant folding or 
+/// desugaring, run with Node::transform. 
+/// 
+/// transform is called bottom-up, on every node once its children have 
+/// already been transformed, and returns the node to put in its place. 
+pub trait Transformer<T: Token> {
+    fn transform(&mut self, node: Node<T>) -> Node<T>;
+}
+
+//Pending work for Node::transform's explicit stack
+enum TransformStep<T: Token> {
+    Visit(Node<T>), 
+    //Rebuild a Composite node from the last n transformed nodes
+    Composite(T, Option<Span>, usize), 
+}
+
+impl<T: Token> Node<T> {
+    ///Rewrites the tree with `t`, children before their parent (see 
+    /// Transformer). A rebuilt Composite node keeps its span when it is 
+    /// passed to `t`. 
+    /// 
+    /// Runs off an explicit stack rather than recursion, so arbitrarily 
+    /// deep trees (long chains of one operator, say) are safe to transform. 
+    pub fn transform(self, t: &mut impl Transformer<T>) -> Node<T> {
+        let mut steps = vec![TransformStep::Visit(self)];
+        let mut done: Vec<Node<T>> = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                TransformStep::Visit(Node::Composite{token, children, span}) => {
+                    steps.push(TransformStep::Composite(token, span, children.len()));
+                    steps.extend(children.into_iter().rev().map(TransformStep::Visit));
+                }, 
+                TransformStep::Visit(node) => done.push(t.transform(node)), 
+                TransformStep::Composite(token, span, len) => {
+                    let children = done.split_off(done.len() - len);
+                    done.push(t.transform(Node::Composite{token, children, span}));
+                }
+            }
+        }
+        done.pop().expect("Node::transform produced no node")
     }
 }
 
@@ -55,13 +497,49 @@ impl<T: Token> Display for Node<T> {
                 Node::Simple(ref t) => format!("Simple({})", t), 
                 Node::Composite{
                     token: ref t, 
-                    children: ref childs
-                } => format!("Composite(token: {}, children: {:?})", t, childs )
+                    children: ref childs, 
+                    ..
+                } => format!("Composite(token: {}, children: {:?})", t, childs ), 
+                Node::Error{ref message, ..} => format!("Error({})", message)
             }
         )
     }
 }
 
+//Deep enough to exercise tree walks, shallow enough not to blow the stack
+#[cfg(feature = "arbitrary")]
+const ARBITRARY_DEPTH: usize = 16;
+
+#[cfg(feature = "arbitrary")]
+impl<'a, T: Token + Arbitrary<'a>> Arbitrary<'a> for Node<T> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Node<T>> {
+        Node::arbitrary_at(u, 0)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<T: Token> Node<T> {
+    fn arbitrary_at<'a>(u: &mut Unstructured<'a>, depth: usize) -> arbitrary::Result<Node<T>>
+        where T: Arbitrary<'a>
+    {
+        if depth >= ARBITRARY_DEPTH || u.is_empty() {
+            return Ok(Node::Simple(u.arbitrary()?))
+        }
+        Ok(match u.int_in_range(0..=2u8)? {
+            0 => Node::Simple(u.arbitrary()?), 
+            1 => {
+                let token = u.arbitrary()?;
+                let len = u.arbitrary_len::<T>()?;
+                let children = (0..len)
+                    .map(|_| Node::arbitrary_at(u, depth + 1))
+                    .collect::<arbitrary::Result<Vec<_>>>()?;
+                Node::Composite{token, children, span: u.arbitrary()?}
+            }, 
+            _ => Node::Error{message: u.arbitrary()?, recovered_at: u.arbitrary()?}
+        })
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -77,4 +555,336 @@ mod test {
         fn assert_sync<T: Sync>() {}
         assert_sync::<Node<String>>();
     }
+
+    fn leaf(s: &str) -> Node<String> {
+        Node::Simple(s.to_string())
+    }
+
+    #[test]
+    fn test_constructors() {
+        assert_eq!(Node::leaf("a".to_string()), Node::Simple("a".to_string()));
+        assert_eq!(Node::branch("+".to_string(), vec![leaf("a"), leaf("b")]), 
+            Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b")], span: None});
+        let aliased: SimpleNode<String> = SimpleNode::leaf("a".to_string());
+        assert_eq!(aliased, leaf("a"));
+    }
+
+    #[test]
+    fn test_from_conversions() {
+        let node: Node<String> = "a".to_string().into();
+        assert_eq!(node, leaf("a"));
+        let node: Node<String> = ("+".to_string(), vec![leaf("a"), leaf("b")]).into();
+        assert_eq!(node, Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b")], span: None});
+        assert_eq!(Node::from(("-".to_string(), vec![])).children(), &[][..]);
+    }
+
+    //Neither Eq, Hash nor Ord
+    #[derive(Clone, Debug, PartialEq)]
+    struct Num(f64);
+
+    impl Display for Num {
+        fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    #[test]
+    fn test_node_over_unordered_token() {
+        let tree = Node::branch(Num(1.5), vec![Node::leaf(Num(2.0)), Node::leaf(Num(f64::NAN))]);
+        assert_eq!(tree.token(), Some(&Num(1.5)));
+        assert_eq!(tree.children()[0], Node::leaf(Num(2.0)));
+        assert_eq!(tree.to_sexpr(), "(1.5 2 NaN)");
+        assert!(tree != tree.clone());
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Tk {
+        Var(char), 
+        Add, 
+        Sub, 
+        Mul, 
+        Pow, 
+        Open, 
+        Close, 
+    }
+
+    impl Display for Tk {
+        fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+            match *self {
+                Tk::Var(c) => write!(f, "{}", c), 
+                Tk::Add => write!(f, "+"), 
+                Tk::Sub => write!(f, "-"), 
+                Tk::Mul => write!(f, "*"), 
+                Tk::Pow => write!(f, "^"), 
+                Tk::Open => write!(f, "("), 
+                Tk::Close => write!(f, ")"), 
+            }
+        }
+    }
+
+    //Parses `src` (one token per char, spaces ignored) and prints it back
+    fn round_trip(src: &str) -> String {
+        use lexer::LexerVec;
+        use parser::{GeneralParser, Parser};
+        use precedence::Associativity;
+
+        let mut spec = ParserSpec::new();
+        spec.add_null_assoc(Tk::Var(' '), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_prefix(Tk::Sub, PrecedenceLevel::Root, PrecedenceLevel::Third, |parser, token, rbp| {
+            Ok(Node::Composite{token, children: vec![parser.parse_expr(rbp)?], span: None})
+        }).unwrap();
+        spec.add_group(Tk::Open, Tk::Close).unwrap();
+        let binary = |parser: &mut dyn Parser<Tk>, token, operand, lhs| {
+            Ok(Node::Composite{token, children: vec![lhs, parser.parse_expr(operand)?], span: None})
+        };
+        spec.add_left_associations(vec![Tk::Add, Tk::Sub], PrecedenceLevel::First, binary).unwrap();
+        spec.add_left_assoc(Tk::Mul, PrecedenceLevel::Second, binary).unwrap();
+        let (operand, bp) = Associativity::Right.binding_powers(PrecedenceLevel::Fourth);
+        spec.add_left_right_assoc(Tk::Pow, operand, bp, binary).unwrap();
+
+        let tokens = src.chars().filter(|c| *c != ' ').map(|c| match c {
+            '+' => Tk::Add, '-' => Tk::Sub, '*' => Tk::Mul, '^' => Tk::Pow, 
+            '(' => Tk::Open, ')' => Tk::Close, c => Tk::Var(c)
+        });
+        let tree = GeneralParser::new(spec.clone(), LexerVec::new(tokens)).parse().unwrap();
+        tree.to_source(&spec)
+    }
+
+    #[test]
+    fn test_to_source_drops_redundant_parens() {
+        assert_eq!(round_trip("a + (b * c)"), "a + b * c");
+        assert_eq!(round_trip("(a * b) + c"), "a * b + c");
+        assert_eq!(round_trip("(a + b) + c"), "a + b + c");
+        assert_eq!(round_trip("a ^ (b ^ c)"), "a ^ b ^ c");
+        assert_eq!(round_trip("-(a * b)"), "-(a * b)");
+        assert_eq!(round_trip("-(a ^ b)"), "-a ^ b");
+        assert_eq!(round_trip("((a))"), "a");
+    }
+
+    #[test]
+    fn test_to_source_keeps_needed_parens() {
+        assert_eq!(round_trip("(a + b) * c"), "(a + b) * c");
+        assert_eq!(round_trip("a - (b - c)"), "a - (b - c)");
+        assert_eq!(round_trip("(a ^ b) ^ c"), "(a ^ b) ^ c");
+        assert_eq!(round_trip("(-a) * b"), "-a * b");
+        assert_eq!(round_trip("a * -(b + c) * d"), "a * -(b + c) * d");
+    }
+
+    #[test]
+    fn test_simple_accessors() {
+        let node = leaf("a");
+        assert_eq!(node.token().map(String::as_str), Some("a"));
+        assert!(node.children().is_empty());
+        assert!(node.into_children().is_empty());
+    }
+
+    #[test]
+    fn test_composite_accessors() {
+        let node = Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b")], span: None};
+        assert_eq!(node.token().map(String::as_str), Some("+"));
+        assert_eq!(node.children(), &[leaf("a"), leaf("b")][..]);
+        assert_eq!(node.into_children(), vec![leaf("a"), leaf("b")]);
+    }
+
+    #[test]
+    fn test_push_child_converts_simple() {
+        let mut node = leaf("-");
+        node.push_child(leaf("a"));
+        assert_eq!(node, Node::Composite{token: "-".to_string(), children: vec![leaf("a")], span: None});
+        node.push_child(leaf("b"));
+        assert_eq!(node.children(), &[leaf("a"), leaf("b")][..]);
+    }
+
+    #[test]
+    fn test_replace_child() {
+        let mut node = Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b")], span: None};
+        assert_eq!(node.replace_child(1, leaf("c")), Some(leaf("b")));
+        assert_eq!(node.children(), &[leaf("a"), leaf("c")][..]);
+        assert_eq!(node.replace_child(2, leaf("d")), None);
+        assert_eq!(leaf("a").replace_child(0, leaf("d")), None);
+    }
+
+    fn binary(op: &str, lhs: Node<String>, rhs: Node<String>) -> Node<String> {
+        Node::Composite{token: op.to_string(), children: vec![lhs, rhs], span: None}
+    }
+
+    #[test]
+    fn test_find_add_nodes() {
+        // (a + b * c) + d
+        let inner = binary("+", leaf("a"), binary("*", leaf("b"), leaf("c")));
+        let tree = binary("+", inner.clone(), leaf("d"));
+
+        assert_eq!(tree.find(|t| t == "+"), Some(&tree));
+        assert_eq!(tree.find(|t| t == "c"), Some(&leaf("c")));
+        assert_eq!(tree.find(|t| t == "-"), None);
+        assert_eq!(tree.find_all(|t| t == "+"), vec![&tree, &inner]);
+
+        let leaves: Vec<&String> = tree.find_all(|t| t.len() == 1 && t.chars().all(char::is_alphabetic))
+            .into_iter().filter_map(Node::token).collect();
+        assert_eq!(leaves, vec!["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_composite_span() {
+        let span = Span{start: 0, end: 5, line: 1, column: 1};
+        let tree = binary("+", leaf("a"), leaf("b"));
+        let mut spanned = Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b")], span: Some(span)};
+        assert_eq!(spanned.span(), Some(span));
+        assert_eq!(tree.span(), None);
+        assert_eq!(leaf("a").span(), None);
+        assert_ne!(spanned, tree);
+        assert!(spanned.structural_eq(&tree));
+
+        spanned.push_child(leaf("c"));
+        assert_eq!(spanned.span(), Some(span));
+        assert_eq!(spanned.strip_spans(), Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b"), leaf("c")], span: None});
+    }
+
+    #[test]
+    fn test_pretty_and_diff() {
+        let tree = binary("+", leaf("a"), binary("*", leaf("b"), leaf("c")));
+        assert_eq!(tree.pretty(), "+\n  a\n  *\n    b\n    c\n");
+
+        let spanned = Node::Composite{token: "*".to_string(), children: vec![leaf("b"), leaf("c")], span: Some(Span{start: 0, end: 1, line: 1, column: 3})};
+        assert_eq!(binary("+", leaf("a"), spanned).pretty(), "+\n  a\n  @1:3 *\n    b\n    c\n");
+
+        let other = binary("+", leaf("a"), binary("*", leaf("b"), leaf("d")));
+        assert_eq!(pretty_diff(&tree, &other), "  +\n    a\n    *\n      b\n-     c\n+     d\n");
+    }
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Lexeme {
+        Ident(String), 
+        Add
+    }
+
+    impl Display for Lexeme {
+        fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    fn sum(lhs: &str, rhs: &str) -> Node<Lexeme> {
+        Node::Composite{token: Lexeme::Add, children: vec![
+            Node::Simple(Lexeme::Ident(lhs.to_string())), 
+            Node::Simple(Lexeme::Ident(rhs.to_string()))
+        ], span: None}
+    }
+
+    #[test]
+    fn test_structural_eq_ignores_lexemes() {
+        assert!(sum("a", "b").structural_eq(&sum("x", "y")));
+        assert_ne!(sum("a", "b"), sum("x", "y"));
+
+        let mut nested = sum("a", "b");
+        nested.replace_child(1, sum("c", "d"));
+        assert!(!sum("a", "b").structural_eq(&nested));
+        assert!(!Node::Simple(Lexeme::Add).structural_eq(&Node::Simple(Lexeme::Ident("a".to_string()))));
+    }
+
+    #[test]
+    fn test_to_postfix() {
+        // a + b * c
+        let tree = binary("+", leaf("a"), binary("*", leaf("b"), leaf("c")));
+        assert_eq!(tree.to_postfix(), vec!["a", "b", "c", "*", "+"]);
+        assert_eq!(leaf("a").to_postfix(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_leaves() {
+        // a + b * c
+        let tree = binary("+", leaf("a"), binary("*", leaf("b"), leaf("c")));
+        assert_eq!(tree.leaves(), vec!["a", "b", "c"]);
+        assert_eq!(leaf("a").leaves(), vec!["a"]);
+        let broken = binary("+", leaf("a"), Node::Error{message: "oops".into(), recovered_at: None});
+        assert_eq!(broken.leaves(), vec!["a"]);
+    }
+
+    #[test]
+    fn test_to_sexpr() {
+        // a + b * c
+        let tree = binary("+", leaf("a"), binary("*", leaf("b"), leaf("c")));
+        assert_eq!(tree.to_sexpr(), "(+ a (* b c))");
+        assert_eq!(leaf("a").to_sexpr(), "a");
+        let spanned = Node::Composite{token: "g".to_string(), children: vec![leaf("a")], span: Some(Span{start: 0, end: 1, line: 1, column: 1})};
+        let error = Node::Error{message: "missing rule".to_string(), recovered_at: None};
+        assert_eq!(Node::branch("f".to_string(), vec![spanned, error]).to_sexpr(), "(f (g a) (error \"missing rule\"))");
+    }
+
+    #[test]
+    fn test_error_node() {
+        let error: Node<String> = Node::Error{message: "missing rule".to_string(), recovered_at: None};
+        assert!(error.is_error());
+        assert_eq!(error.token(), None);
+        assert!(error.children().is_empty());
+        assert!(error.structural_eq(&Node::Error{message: "other".to_string(), recovered_at: None}));
+
+        let tree = binary("+", leaf("a"), error);
+        assert_eq!(tree.to_postfix(), vec!["a", "+"]);
+        assert_eq!(tree.find_all(|_| true).len(), 2);
+    }
+
+    //Counts the leaves of a tree, rejecting Error nodes
+    struct LeafCount(usize);
+
+    impl Lower<String> for LeafCount {
+        fn lower(node: Node<String>) -> Result<LeafCount, LowerError> {
+            match node {
+                Node::Simple(_) => Ok(LeafCount(1)), 
+                Node::Composite{children, ..} => children.into_iter()
+                    .map(|child| child.lower_into::<LeafCount>().map(|count| count.0))
+                    .sum::<Result<usize, LowerError>>()
+                    .map(LeafCount), 
+                node => Err(LowerError::unexpected(&node))
+            }
+        }
+    }
+
+    #[test]
+    fn test_lower_into() {
+        let tree = binary("+", leaf("a"), binary("*", leaf("b"), leaf("c")));
+        assert_eq!(tree.lower_into::<LeafCount>().map(|count| count.0), Ok(3));
+
+        let error = Node::Error{message: "oops".to_string(), recovered_at: None};
+        let tree = binary("+", leaf("a"), error);
+        assert_eq!(tree.lower_into::<LeafCount>().map(|count| count.0), 
+            Err(LowerError::UnexpectedNode{node: "Error(oops)".to_string()}));
+    }
+    //Folds `+` over two numeric leaves into their sum
+    struct FoldAdd;
+
+    impl Transformer<String> for FoldAdd {
+        fn transform(&mut self, node: Node<String>) -> Node<String> {
+            let sum = match node {
+                Node::Composite{ref token, ref children, ..} if token == "+" => children.iter()
+                    .map(|child| match *child {
+                        Node::Simple(ref n) => n.parse::<i64>().ok(), 
+                        _ => None
+                    })
+                    .sum::<Option<i64>>(), 
+                _ => None
+            };
+            match sum {
+                Some(sum) => Node::Simple(sum.to_string()), 
+                None => node
+            }
+        }
+    }
+
+    #[test]
+    fn test_transform_folds_constants() {
+        let tree = binary("+", leaf("1"), leaf("2"));
+        assert_eq!(tree.transform(&mut FoldAdd), leaf("3"));
+
+        //Bottom-up, so folded children let their parent fold too
+        let tree = binary("*", binary("+", binary("+", leaf("1"), leaf("2")), leaf("3")), 
+            binary("+", leaf("x"), binary("+", leaf("4"), leaf("5"))));
+        assert_eq!(tree.transform(&mut FoldAdd), 
+            binary("*", leaf("6"), binary("+", leaf("x"), leaf("9"))));
+
+        let span = Span{start: 0, end: 5, line: 1, column: 1};
+        let tree = Node::Composite{token: "*".to_string(), children: vec![binary("+", leaf("1"), leaf("2")), leaf("x")], span: Some(span)};
+        assert_eq!(tree.transform(&mut FoldAdd), 
+            Node::Composite{token: "*".to_string(), children: vec![leaf("3"), leaf("x")], span: Some(span)});
+    }
 }
\ No newline at end of file
@@ -34,12 +34,37 @@
 //! 
 //! The GeneralParser implementation here requires a provided ParserSpec and Lexer 
 //! containing the tokens to be parsed. 
+//! 
+//! ## Panics
+//! 
+//! GeneralParser and BacktrackingParser don't panic on bad input: whatever 
+//! the token stream (empty, truncated, unbalanced), every entry point returns 
+//! a ParseError, provided the lexer keeps the Lexer contract (peek returns 
+//! Some exactly when next_token has a token to give, as the built-in lexers 
+//! do). Panics raised by the rules themselves, such as an ```unreachable!()``` 
+//! in a rule that meets a token it didn't expect, are not caught and unwind 
+//! through the parser; rules should return a ParseError instead. 
+//! 
+//! Deeply nested input is the one exception. Every level of nesting (an 
+//! opening token, a prefix operator, the operand of a right associative 
+//! operator) recurses once, so input nested a few thousand levels deep 
+//! (fewer in a debug build) overflows the stack, which aborts the process 
+//! rather than panicking. 
+//! Nesting is unlimited by default: on untrusted input, bound bracket depth 
+//! with ParserSpec::set_max_nesting, which makes it NestingTooDeep, and the 
+//! length of the input for the rest. 
 
 use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
 use std::marker::{Send, Sync};
-use std::mem::{Discriminant, discriminant};
+use std::ops::{Deref, DerefMut, Range};
+use std::sync::Arc;
 
+use precedence::PrecedenceTable;
+use spec::{FrozenSpec, IntoFrozenSpec};
 use prelude::*;
+use token::{DiscriminantKey, KeyFn, TokenMatcher};
 use types::*;
 
 /// Parser trait. Theoretically, one could use different parser impls during parse of a 
@@ -47,36 +72,451 @@ use types::*;
 /// Hence why the ParserSpec uses closures with the signature ```&mut dyn Parser<T>```
 pub trait Parser<T: Token + Send + Sync + 'static> {
     fn parse(&mut self) -> Result<Node<T>, ParseError<T>>;
+    /// The recursion primitive rules parse their operands with: parses a 
+    /// null rule, then keeps applying left rules while the next token binds 
+    /// tighter than rbp. An operator at exactly rbp is left for the caller. 
+    /// parse_expr_left and parse_expr_right pick rbp for an associativity, 
+    /// for rules that want a different one than their token was registered with. 
     fn parse_expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>>;
     /// parse_sequence impl can be a bit complex - 
     /// basically it *should* call parse_expr repeatedly with prec_level, 
     /// while consuming an (optional) separator token, and then consuming 
-    /// an end token, or if there is no end token, consuming until we reach Incomplete
+    /// an end token, or if there is no end token, consuming until the input runs out
     fn parse_sequence(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>>;
     fn next_binds_tighter_than(&mut self, rbp: PrecedenceLevel) -> bool;
     fn consume(&mut self, end_token: T) -> Result<(), ParseError<T>>;
+
+    // Everything below has a default written in terms of the methods above, 
+    // so implementations of the original five keep compiling. The defaults 
+    // that need to look ahead go through peek. 
+
+    /// The next token, without consuming it. The default can't look ahead 
+    /// and returns None, which the lookahead-based defaults (at_end, 
+    /// consume_if, parse_optional, skip_balanced, parse_sequence_all) take 
+    /// for the end of the input, so implementations should override it. 
+    fn peek(&self) -> Option<T> {
+        None
+    }
+    /// parse_sequence, but giving up with ParseError::SequenceTooLong as the 
+    /// last result once `max` items have been parsed and another one follows. 
+    /// Use it on untrusted input to bound how much a single list can allocate. 
+    /// The default parses the whole list with parse_sequence and cuts it 
+    /// down afterwards, so it only bounds the result, not the work. 
+    fn parse_sequence_bounded(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>, max: usize) -> Vec<Result<Node<T>, ParseError<T>>> {
+        let mut results = self.parse_sequence(prec_level, sep, end_token);
+        if results.len() > max {
+            results.truncate(max);
+            results.push(Err(ParseError::SequenceTooLong{limit: max}));
+        }
+        results
+    }
+    /// parse_sequence, but an item that fails to parse, or isn't followed by 
+    /// a separator, doesn't end the list: its error is recorded, the tokens 
+    /// up to the next separator are skipped, and parsing goes on. Every 
+    /// malformed item gets its own error, for validating a whole list at once. 
+    /// The list still ends at the end token (or the end of input). 
+    fn parse_sequence_all(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>> {
+        let mut results = Vec::new();
+        loop {
+            let mut items = self.parse_sequence(prec_level, sep.clone(), end_token.clone());
+            let failed = items.last().is_some_and(Result::is_err);
+            results.append(&mut items);
+            if !failed {
+                return results
+            }
+            //Skip to the next separator (and go on) or end token (and stop)
+            loop {
+                let tk = match self.peek() {
+                    Some(tk) => tk, 
+                    None => return results
+                };
+                let (at_sep, at_end) = (sep.as_ref() == Some(&tk), end_token.as_ref() == Some(&tk));
+                if let Err(err) = self.consume(tk) {
+                    results.push(Err(err));
+                    return results
+                }
+                if at_end {
+                    return results
+                }
+                if at_sep {
+                    break
+                }
+            }
+        }
+    }
+    /// Consumes the next token if it equals any of `tokens`, returning it, 
+    /// e.g. for a closing bracket that may be either ```)``` or ```]```. 
+    fn consume_one_of(&mut self, tokens: &[T]) -> Result<T, ParseError<T>> {
+        let mut found = None;
+        for tk in tokens {
+            match self.consume(tk.clone()) {
+                Ok(()) => return Ok(tk.clone()), 
+                Err(ParseError::ConsumeFailed{found: next, ..}) => found = Some(next), 
+                Err(err) => return Err(err)
+            }
+        }
+        match found.or_else(|| self.peek()) {
+            Some(found) => Err(ParseError::ConsumeOneFailed{expected: tokens.to_vec(), found}), 
+            None => Err(ParseError::UnexpectedEof)
+        }
+    }
+    /// Consumes the next token if `matcher` accepts it, returning it. Unlike 
+    /// consume, what counts as a match is spelled out by the matcher (see 
+    /// token::Exact and token::SameKind) rather than left to PartialEq. 
+    fn consume_if(&mut self, matcher: &dyn TokenMatcher<T>) -> Result<T, ParseError<T>> {
+        match self.peek() {
+            Some(tk) if matcher.matches(&tk) => self.consume(tk.clone()).map(|_| tk), 
+            Some(tk) => Err(ParseError::ConsumeIfFailed{found: tk}), 
+            None => Err(ParseError::UnexpectedEof)
+        }
+    }
+    /// Parses an expression at rbp unless the next token is one of the 
+    /// terminators (or the input is exhausted), in which case Ok(None) is 
+    /// returned and nothing is consumed. 
+    /// For optional parts such as the expression in ```return expr? ;```
+    fn parse_optional(&mut self, rbp: PrecedenceLevel, terminators: &[T]) -> Result<Option<Node<T>>, ParseError<T>> {
+        match self.peek() {
+            Some(ref tk) if !terminators.contains(tk) => self.parse_expr(rbp).map(Some), 
+            _ => Ok(None)
+        }
+    }
+    /// Parses zero or more expressions at rbp until the next token equals 
+    /// stop, then consumes stop and returns the collected nodes. 
+    /// For bodies like ```{ item item item }``` where the open brace was 
+    /// already consumed by the caller. 
+    fn parse_repeated(&mut self, rbp: PrecedenceLevel, stop: &T) -> Result<Vec<Node<T>>, ParseError<T>> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.consume(stop.clone()) {
+                Ok(()) => return Ok(nodes), 
+                Err(ParseError::ConsumeFailed{..}) => nodes.push(self.parse_expr(rbp)?), 
+                Err(err) => return Err(err)
+            }
+        }
+    }
+    /// True when there are no more tokens, for rules deciding whether an 
+    /// optional part follows without having to catch Incomplete. 
+    fn at_end(&self) -> bool {
+        self.peek().is_none()
+    }
+    /// Inserts `tokens` in front of the remaining input (see Lexer::splice), 
+    /// e.g. for a rule that expands a macro invocation into its body and 
+    /// carries on parsing. Fails with SpliceUnsupported if the lexer can't 
+    /// insert tokens, as the default always does. 
+    fn splice(&mut self, _tokens: Vec<T>) -> Result<(), ParseError<T>> {
+        Err(ParseError::SpliceUnsupported)
+    }
+    /// Parses a delimited, separated list such as call arguments 
+    /// ```( a, b, c )```: consumes open, then items at item_bp separated by 
+    /// sep, then close. ```()``` gives an empty list; a trailing separator 
+    /// is not accepted. 
+    fn parse_args(&mut self, open: T, sep: T, close: T, item_bp: PrecedenceLevel) -> Result<Vec<Node<T>>, ParseError<T>> {
+        self.consume(open)?;
+        let mut args = Vec::new();
+        match self.consume(close.clone()) {
+            Ok(()) => return Ok(args), 
+            Err(ParseError::ConsumeFailed{..}) => {}, 
+            Err(err) => return Err(err)
+        }
+        let delimiters = [sep, close];
+        loop {
+            args.push(self.parse_expr(item_bp)?);
+            if self.consume_one_of(&delimiters)? == delimiters[1] {
+                return Ok(args)
+            }
+        }
+    }
+    /// Parses exactly `n` expressions at rbp, for fixed-arity constructs such 
+    /// as a three-operand instruction. Stops at the first error; running out 
+    /// of input before the n-th expression is UnexpectedEof. 
+    fn parse_n(&mut self, rbp: PrecedenceLevel, n: usize) -> Result<Vec<Node<T>>, ParseError<T>> {
+        let mut nodes = Vec::with_capacity(n);
+        for _ in 0..n {
+            nodes.push(self.parse_expr(rbp).map_err(|err| match err {
+                ParseError::EmptyInput | ParseError::Incomplete => ParseError::UnexpectedEof, 
+                err => err
+            })?);
+        }
+        Ok(nodes)
+    }
+    /// Skips a bracketed region without parsing it: consumes `open` (which 
+    /// must be the next token), then every token up to the `close` matching 
+    /// it, tracking nesting. Returns the tokens between the two, nested 
+    /// brackets included, e.g. to step over a malformed block and resume. 
+    /// Running out of input before the matching close is Incomplete. 
+    fn skip_balanced(&mut self, open: T, close: T) -> Result<Vec<T>, ParseError<T>> {
+        self.consume(open.clone())?;
+        let mut inner = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            let tk = self.peek().ok_or(ParseError::Incomplete)?;
+            self.consume(tk.clone())?;
+            if tk == close {
+                if depth == 0 {
+                    return Ok(inner)
+                }
+                depth -= 1;
+            } else if tk == open {
+                depth += 1;
+            }
+            inner.push(tk);
+        }
+    }
+    /// Consumes the close token registered for `open` with 
+    /// ParserSpec::add_delimiters, returning it, for bracket rules that 
+    /// shouldn't spell out which closer goes with which opener. Another 
+    /// token in its place is ParseError::MismatchedDelimiter; an `open` 
+    /// without a registered pair is MissingRule, which is all the default, 
+    /// having no spec to look pairs up in, can say. 
+    fn consume_matching(&mut self, open: &T) -> Result<T, ParseError<T>> {
+        Err(ParseError::MissingRule{token: open.clone(), ty: "Delimiter".into()})
+    }
+    /// parse_expr, but the expression also ends before any of `stop_tokens`, 
+    /// even one with a left rule that would bind at rbp: for list items 
+    /// such as call arguments, where ```,``` separates rather than operates 
+    /// (C's comma operator). The stop applies to the whole subexpression, 
+    /// except inside brackets opened within it (see ParserSpec::add_opening), 
+    /// so ```f((a, b), c)``` still nests. The default can't reach inside 
+    /// parse_expr and ignores `stop_tokens`. 
+    fn parse_expr_with_floor(&mut self, rbp: PrecedenceLevel, _stop_tokens: &[T]) -> Result<Node<T>, ParseError<T>> {
+        self.parse_expr(rbp)
+    }
+    /// parse_expr for the operand of a right associative operator at 
+    /// `level`: recurses at level.prev(), so another operator at `level` 
+    /// binds inside the operand and ```a ^ b ^ c``` groups as ```a ^ (b ^ c)```. 
+    fn parse_expr_right(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        self.parse_expr(level.prev())
+    }
+    /// parse_expr for the operand of a left associative operator at 
+    /// `level`: recurses at `level` itself, so another operator at `level` 
+    /// is left to the caller and ```a - b - c``` groups as ```(a - b) - c```. 
+    fn parse_expr_left(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        self.parse_expr(level)
+    }
+}
+
+/// Parse decisions reported to the hook set with GeneralParser::set_trace. 
+#[derive(Clone, Debug, PartialEq)]
+pub enum TraceEvent<T> {
+    /// parse_expr was entered with this right binding power. 
+    EnterExpr {rbp: PrecedenceLevel}, 
+    /// The null rule of *token* is about to run. 
+    NullRule {token: T}, 
+    /// The left rule of *token* is about to run. 
+    LeftRule {token: T}, 
+    /// consume (or consume_one_of) accepted *token*. 
+    Consume {token: T}, 
+    /// parse_expr returned; *node_summary* is the node's Display form, or 
+    /// the error's if it failed. 
+    Exit {node_summary: String}, 
+}
+
+type TraceHook<T> = Box<dyn FnMut(TraceEvent<T>) + Send + Sync>;
+type EventedParse<T> = (Result<Node<T>, ParseError<T>>, Vec<ParseEvent<T>>);
+
+/// What part a token played in the parse, as reported by 
+/// GeneralParser::parse_with_events. 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum TokenRole {
+    /// A token whose null rule parsed nothing further: an identifier, a literal. 
+    Operand, 
+    /// A token whose left rule ran, or whose null rule parsed a subexpression 
+    /// (a prefix operator). 
+    Operator, 
+    /// A token taken by consume and friends, such as a closing bracket or a 
+    /// separator, or a null rule token that both parsed a subexpression and 
+    /// consumed one (an opening bracket). 
+    Delimiter, 
+}
+
+/// A token consumed by GeneralParser::parse_with_events, with its role and 
+/// where it came from: its lexer position, and its span if the lexer has 
+/// them. 
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseEvent<T> {
+    pub token: T, 
+    pub role: TokenRole, 
+    pub position: Option<usize>, 
+    pub span: Option<Span>, 
 }
 
+///A type-erased lexer, for picking the lexer at runtime. Send and Sync keep a 
+/// GeneralParser over it Send and Sync. 
+pub type BoxedLexer<T> = Box<dyn Lexer<T> + Send + Sync>;
+
+type Alternatives<T, K, V> = HashMap<<K as KeyFn<T>>::Key, Vec<V>>;
+
+//parse_expr results by (start position, rbp), with the position they end at
+type Memo<T> = HashMap<(usize, PrecedenceLevel), (Result<Node<T>, ParseError<T>>, usize)>;
+
+//Old subtrees for reparse by the offset they start at, with the offset they 
+// end at and whether they are a bracketed group
+type Reusable<T> = HashMap<usize, (Node<T>, usize, bool)>;
+
+//Lexer position and the consumed and spliced counts where a loop item started
+type ProgressMark = (Option<usize>, usize, usize);
+
 /// General implementation of Parser trait. This implementation should work for any 
 /// valid set of Syntax rules. 
 /// A second generic, `L`, is added in order to allow us to decouple this impl from any specific 
 /// Lexer impl. 
-/// Here we just copy and consume the fields of the ParserSpec in order to set this up.
-/// If we instead held a ParserSpec internally, we get borrow checker error messages. 
-/// Consider: 
+/// The spec is held frozen (see ParserSpec::freeze), so parsers made from 
+/// one FrozenSpec share its rules. 
+/// Looking a rule up borrows self.spec, and a rule needs `&mut self` to run: 
 /// ```Rust
-/// let null_info = self.spec.get_null(&tk);
+/// let null_info = self.spec.null_rule(&tk);
 /// ```
-/// This inherently borrows self.spec, which then borrows self as an outcome. 
-/// If instead you own the HashMaps, only those specific members are considered 
-/// borrowed by borrowck. 
-pub struct GeneralParser<T, L>
+/// so the rule info is cloned out of the spec before it's called, which only 
+/// bumps the Arc reference count of the rule. 
+pub struct GeneralParser<T, L, K = DiscriminantKey, S = RandomState>
     where T: Token + Send + Sync + 'static, 
-          L: Lexer<T>
+          L: Lexer<T>, 
+          K: KeyFn<T>, 
+          S: BuildHasher
 {
-    null_map: HashMap<Discriminant<T>, NullInfo<T>>, 
-    left_map: HashMap<Discriminant<T>, LeftInfo<T>>,
+    spec: FrozenSpec<T, K, S>,
     lexer: L, 
+    //Lookahead slot: None means the lexer hasn't been peeked since the last
+    // advance, Some(None) means it was peeked and is exhausted. 
+    peeked: Option<Option<T>>,
+    //Arc so that fork can share it
+    precedence_table: Option<Arc<dyn PrecedenceTable<T>>>,
+    recover_errors: bool,
+    //In spanned mode, the span of the last token handed to a rule
+    spanned: bool,
+    last_span: Option<Span>,
+    //Number of parse_expr calls in progress, to tell running out of input 
+    // between expressions (Incomplete) from running out inside one (UnexpectedEof)
+    depth: usize,
+    //Opening tokens whose null rule is running, for ParserSpec::set_max_nesting
+    nesting: usize,
+    //Tokens that end the expression, set by parse_expr_with_floor
+    stops: Vec<T>,
+    //Whether any token has been consumed, to tell an empty input (EmptyInput) 
+    // from one that ran out between expressions (Incomplete)
+    started: bool,
+    //Number of tokens consumed, for telling whether a failed parse moved at all
+    consumed: usize,
+    //Number of tokens put back through splice, so a rule that splices its own 
+    // token back doesn't count as progress
+    spliced: usize,
+    //The token advance last took, so parse_program can tell whether a failed 
+    // statement already ate its terminator
+    last_taken: Option<T>,
+    trace: Option<TraceHook<T>>,
+    //Rules a BacktrackingParser falls back on, and how it steps the lexer 
+    // back to retry them. Both stay empty for a plain GeneralParser. 
+    null_alternatives: Alternatives<T, K, NullInfo<T>>,
+    left_alternatives: Alternatives<T, K, LeftInfo<T>>,
+    rewind: Option<fn(&mut L) -> bool>,
+    memo: Option<Memo<T>>,
+    //Consumed tokens by variant, once enable_kind_counts turns counting on
+    count_kinds: bool,
+    kinds: HashMap<K::Key, usize>,
+    //Collected by parse_with_events, each with the depth it was recorded at
+    events: Option<Vec<(ParseEvent<T>, usize)>>,
+    //Subtrees reparse takes from the old tree instead of parsing them again, 
+    // by the offset they start at, and how many it took
+    reuse: Reusable<T>,
+    reused: usize,
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> GeneralParser<T, BoxedLexer<T>, K, S> {
+    ///Like new, but over a boxed lexer, so that parsers reading from different 
+    /// kinds of lexer share one type and can be stored together. 
+    pub fn new_boxed(spec: impl IntoFrozenSpec<T, Key=K, Hasher=S>, lexer: BoxedLexer<T>) -> GeneralParser<T, BoxedLexer<T>, K, S> {
+        GeneralParser::new(spec, lexer)
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, L: Lexer<T> + Clone, K: KeyFn<T>, S: BuildHasher> GeneralParser<T, L, K, S> {
+    ///An independent parser at the same position, for a speculative parse 
+    /// that mustn't move this one: the fork gets a copy of the lexer and 
+    /// shares the rules (the spec, alternatives and precedence table are 
+    /// behind Arcs, so nothing is deep-copied but the lexer). Parse on the 
+    /// fork, and if the attempt is worth keeping, parse it again here. 
+    /// 
+    /// The fork starts without a trace hook, and with an empty memo table 
+    /// if memoization is on. 
+    pub fn fork(&self) -> GeneralParser<T, L, K, S> {
+        GeneralParser {
+            spec: self.spec.clone(),
+            lexer: self.lexer.clone(), 
+            peeked: self.peeked.clone(),
+            precedence_table: self.precedence_table.clone(),
+            recover_errors: self.recover_errors,
+            spanned: self.spanned,
+            last_span: self.last_span,
+            depth: self.depth,
+            nesting: self.nesting,
+            stops: self.stops.clone(),
+            started: self.started,
+            consumed: self.consumed,
+            spliced: self.spliced,
+            last_taken: self.last_taken.clone(),
+            trace: None,
+            null_alternatives: self.null_alternatives.clone(),
+            left_alternatives: self.left_alternatives.clone(),
+            rewind: self.rewind,
+            memo: self.memo.as_ref().map(|_| HashMap::new()),
+            count_kinds: self.count_kinds,
+            kinds: self.kinds.clone(),
+            events: None,
+            reuse: HashMap::new(),
+            reused: 0,
+        }
+    }
+
+    ///Parses the lexer's input, an edited version of the source `old_tree` 
+    /// was parsed from, reusing the parts of `old_tree` the edit can't have 
+    /// changed instead of running their rules again. `changed_range` is the 
+    /// byte range of the new source that differs from the old one. 
+    /// 
+    /// `old_tree` must be the spanned tree (see set_spanned) of the whole old 
+    /// input, and this parser has to be fresh, over a lexer that reports 
+    /// spans; reparse switches spanned mode on. Text outside `changed_range` 
+    /// must be unchanged; text after it may have moved, and the spans of 
+    /// reused subtrees are moved with it. 
+    /// 
+    /// What gets reused is the result of a null rule (an operand, such as a 
+    /// parenthesized expression), when its tokens and the token after it are 
+    /// clear of the edit. The rules must not depend on anything but the 
+    /// input, as for enable_memoization. Only Composite nodes carry spans, so 
+    /// a subtree whose left operand is a leaf can't be told apart from a null 
+    /// rule's result; unless it is bracketed, it is only reused where its 
+    /// operator would bind again, which assumes left rules build their node 
+    /// on their own token. 
+    pub fn reparse(&mut self, old_tree: &Node<T>, changed_range: Range<usize>) -> Result<Node<T>, ParseError<T>> {
+        let mut lexer = self.lexer.clone();
+        let mut spans = Vec::new();
+        let mut closers = Vec::new();
+        let mut open = Vec::new();
+        while let Some(span) = lexer.current_span() {
+            let tk = match lexer.try_next_token() {
+                Some(tk) => tk, 
+                None => break
+            };
+            if self.spec.is_opening(&tk) {
+                open.push(spans.len());
+            } else if self.spec.is_closing(&tk) {
+                if let Some(opener) = open.pop() {
+                    closers[opener] = Some(spans.len());
+                }
+            }
+            spans.push(span);
+            closers.push(None);
+        }
+        self.reuse.clear();
+        self.reused = 0;
+        if let (Some(old), Some(new)) = (old_tree.span(), spans.last().copied()) {
+            let edit = Edit{changed: changed_range, shift: new.end as isize - old.end as isize, spans, closers};
+            edit.collect(old_tree, &mut self.reuse);
+        }
+        self.spanned = true;
+        let res = self.parse();
+        self.reuse.clear();
+        res
+    }
 }
 
 /// GeneralParser impl
@@ -84,160 +524,2565 @@ pub struct GeneralParser<T, L>
 /// the trait. Also offers a compile time check that GeneralParser still
 /// impls Parser correctly. 
 #[allow(dead_code)]
-impl<T: Token + Send + Sync + 'static, L: Lexer<T>> GeneralParser<T, L> {
-    pub fn new(spec: ParserSpec<T>, lexer: L) -> GeneralParser<T, L> {
-        let (null_map, left_map) = spec.maps();
+impl<T: Token + Send + Sync + 'static, L: Lexer<T>, K: KeyFn<T>, S: BuildHasher> GeneralParser<T, L, K, S> {
+    ///Takes a ParserSpec, or a FrozenSpec to share one spec between parsers. 
+    pub fn new(spec: impl IntoFrozenSpec<T, Key=K, Hasher=S>, lexer: L) -> GeneralParser<T, L, K, S> {
         GeneralParser {
-            null_map: null_map,
-            left_map: left_map,
-            lexer: lexer
+            spec: spec.into_frozen(),
+            lexer, 
+            peeked: None,
+            precedence_table: None,
+            recover_errors: false,
+            spanned: false,
+            last_span: None,
+            depth: 0,
+            nesting: 0,
+            stops: Vec::new(),
+            started: false,
+            consumed: 0,
+            spliced: 0,
+            last_taken: None,
+            trace: None,
+            null_alternatives: HashMap::new(),
+            left_alternatives: HashMap::new(),
+            rewind: None,
+            memo: None,
+            count_kinds: false,
+            kinds: HashMap::new(),
+            events: None,
+            reuse: HashMap::new(),
+            reused: 0,
         }
     }
 
-    fn parse(&mut self) -> Result<Node<T>, ParseError<T>> {
-        self.parse_expr(PrecedenceLevel::Root)
+    ///Number of subtrees the last reparse took from the old tree instead of 
+    /// parsing them. 
+    pub fn reused(&self) -> usize {
+        self.reused
     }
 
-    fn parse_expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+    ///Caches the result of every parse_expr call by the position it started 
+    /// at and its rbp, so parsing the same input at the same level again 
+    /// (as backtracking does) replays the result instead of running the rules. 
+    /// The rules must not depend on anything but the input for this to be 
+    /// sound. A hit still has to step the lexer over the tokens the cached 
+    /// expression covers, but no rule runs. 
+    /// 
+    /// Needs a lexer that reports its position. The cache is dropped when 
+    /// tokens are spliced in. 
+    pub fn enable_memoization(&mut self) {
+        if self.memo.is_none() {
+            self.memo = Some(HashMap::new());
+        }
+    }
+
+    ///Counts the tokens the parser consumes from here on by their key (see 
+    /// KeyFn; the enum variant with the default DiscriminantKey), for tooling that asks "did this parse include any Mul tokens?" 
+    /// without walking the tree (see consumed_kinds). Off by default, since 
+    /// it costs a hash map update per token. Tokens a BacktrackingParser 
+    /// steps back over and consumes again are counted each time. 
+    pub fn enable_kind_counts(&mut self) {
+        self.count_kinds = true;
+    }
+
+    ///How many tokens of each key were consumed since enable_kind_counts. 
+    /// Empty if counting is off. 
+    pub fn consumed_kinds(&self) -> &HashMap<K::Key, usize> {
+        &self.kinds
+    }
+
+    ///Calls `f` with a TraceEvent for each decision the parser makes (entering 
+    /// and leaving parse_expr, running rules, consuming tokens), for seeing 
+    /// how a grammar actually parses without sprinkling prints through the 
+    /// rules. Without a hook, tracing costs a branch per event. 
+    pub fn set_trace(&mut self, f: impl FnMut(TraceEvent<T>) + Send + Sync + 'static) {
+        self.trace = Some(Box::new(f));
+    }
+
+    ///Removes the hook set with set_trace. 
+    pub fn clear_trace(&mut self) {
+        self.trace = None;
+    }
+
+    ///Switches error recovery on or off (it is off by default). 
+    /// 
+    /// While on, a MissingRule, LexicalError or consume error (ConsumeFailed, 
+    /// ConsumeOneFailed, ConsumeIfFailed, MismatchedDelimiter) inside 
+    /// an expression doesn't abort the parse: the failed subexpression is replaced by a 
+    /// Node::Error and parsing carries on, producing a best-effort tree for 
+    /// tooling that needs one even for broken input. Other errors are still 
+    /// returned as Err. 
+    pub fn set_error_recovery(&mut self, enabled: bool) {
+        self.recover_errors = enabled;
+    }
+
+    ///Switches spanned mode on or off (it is off by default). 
+    /// 
+    /// While on, every Composite node a rule returns gets the span of its 
+    /// tokens (see Node::span), from the token its null rule started at to 
+    /// the last token consumed for it. Simple nodes are left without one. 
+    /// This needs a lexer that reports spans (see Lexer::current_span); with 
+    /// any other lexer no span is set. 
+    pub fn set_spanned(&mut self, enabled: bool) {
+        self.spanned = enabled;
+    }
+
+    ///Makes the parser ask `table` for the binding power of operators instead 
+    /// of using the level baked into the spec. See PrecedenceTable. 
+    pub fn set_precedence_table(&mut self, table: impl PrecedenceTable<T> + 'static) {
+        self.precedence_table = Some(Arc::new(table));
+    }
+
+    ///Goes back to using the levels registered in the spec. 
+    pub fn clear_precedence_table(&mut self) {
+        self.precedence_table = None;
+    }
+
+    ///Parses one expression, starting at the spec's entry level (see 
+    /// ParserSpec::set_entry). The same as Parser::parse, callable without 
+    /// importing the trait: 
+    /// 
+    /// ```rust
+    /// # extern crate prattle;
+    /// use prattle::lexer::LexerVec;
+    /// use prattle::node::Node;
+    /// use prattle::parser::GeneralParser;
+    /// use prattle::precedence::PrecedenceLevel;
+    /// use prattle::spec::ParserSpec;
+    /// 
+    /// # fn main() {
+    /// let mut spec = ParserSpec::new();
+    /// spec.add_null_assoc("a".to_string(), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+    /// let mut parser = GeneralParser::new(spec, LexerVec::new(vec!["a".to_string(), ";".to_string()]));
+    /// assert_eq!(parser.parse(), Ok(Node::Simple("a".to_string())));
+    /// assert_eq!(parser.consume(";".to_string()), Ok(()));
+    /// # }
+    /// ```
+    pub fn parse(&mut self) -> Result<Node<T>, ParseError<T>> {
+        <Self as Parser<T>>::parse(self)
+    }
+
+    ///Parses a single expression, starting at the given entry precedence. 
+    /// 
+    /// Only operators that bind tighter than `entry` are folded into the 
+    /// result, so grammars with several entry points (say, statements at 
+    /// Root and bare expressions at First) can pick where to start instead 
+    /// of always beginning at the spec's entry level as parse() does. 
+    pub fn parse_from(&mut self, entry: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        <Self as Parser<T>>::parse_expr(self, entry)
+    }
+
+    ///Alias for parse_from, for call sites that read better as "parse an 
+    /// expression at this level". 
+    pub fn parse_expression(&mut self, entry: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        self.parse_from(entry)
+    }
+
+    ///Parses an expression, folding in operators that bind tighter than 
+    /// `rbp`. The same as Parser::parse_expr; see it for the details. 
+    pub fn parse_expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
         <Self as Parser<T>>::parse_expr(self, rbp)
     }
+
+    ///Lazily parses top-level expressions, one per call to next(), without 
+    /// collecting them. Only the parser's own state is held, so arbitrarily 
+    /// long token streams can be processed in constant memory, and dropping 
+    /// the stream early leaves the rest of the input unparsed. 
+    /// 
+    /// Unlike iterating the parser directly, the stream ends after the first 
+    /// error, since resuming partway through a broken expression rarely 
+    /// produces anything useful. That includes ParseError::NoProgress for a 
+    /// rule that splices its own token back, so such a grammar ends the 
+    /// stream instead of yielding the same node forever. 
+    pub fn parse_stream<'a>(&'a mut self) -> impl Iterator<Item=Result<Node<T>, ParseError<T>>> + 'a {
+        let mut failed = false;
+        ::std::iter::from_fn(move || {
+            if failed {
+                return None
+            }
+            let item = self.next()?;
+            failed = item.is_err();
+            Some(item)
+        })
+    }
+
+    ///Parses top-level statements until the input runs out. 
+    /// 
+    /// If the spec has a statement terminator (see 
+    /// ParserSpec::set_statement_terminator), it is consumed after each 
+    /// statement; only the last one may leave it off. A statement that fails 
+    /// is reported and parsing resumes after the next terminator (or right 
+    /// away, if the failure already took it), so one broken statement 
+    /// doesn't hide the rest. Without a terminator there is nowhere to 
+    /// resume from, so parsing stops at the first error. 
+    pub fn parse_program(&mut self) -> Vec<Result<Node<T>, ParseError<T>>> {
+        let mut statements = Vec::new();
+        while self.peek_token().is_some() {
+            let consumed = self.consumed;
+            let res = match self.parse() {
+                Ok(node) => self.end_statement().map(|_| node), 
+                Err(err) => Err(err)
+            };
+            let failed = res.is_err();
+            statements.push(res);
+            if failed {
+                match self.spec.statement_terminator().cloned() {
+                    Some(ref terminator) if self.consumed != consumed 
+                        && self.last_taken.as_ref() == Some(terminator) => {}, 
+                    Some(terminator) => self.skip_past(&terminator), 
+                    None => break
+                }
+            }
+        }
+        statements
+    }
+
+    ///All-or-nothing parse_program, for batch tools: the statements if every 
+    /// one parsed, otherwise the errors of all the ones that didn't (parsing 
+    /// resumes after each as parse_program does, so they are all reported). 
+    pub fn parse_program_strict(&mut self) -> Result<Vec<Node<T>>, Vec<ParseError<T>>> {
+        let (nodes, errors): (Vec<_>, Vec<_>) = self.parse_program().into_iter().partition(Result::is_ok);
+        if errors.is_empty() {
+            Ok(nodes.into_iter().filter_map(Result::ok).collect())
+        } else {
+            Err(errors.into_iter().filter_map(Result::err).collect())
+        }
+    }
+
+    ///Parses one expression, as parse() does, and also returns the lexer's 
+    /// position afterwards (see Lexer::position; 0 if the lexer can't tell), 
+    /// so a REPL can evaluate the expression and echo or re-parse what's left. 
+    pub fn parse_one(&mut self) -> (Result<Node<T>, ParseError<T>>, usize) {
+        let res = self.parse();
+        (res, self.lexer.position().unwrap_or(0))
+    }
+
+    ///Parses one expression, as parse() does, and also returns each token 
+    /// it consumed in order, with the role it played (see TokenRole), for 
+    /// editors that colour tokens by what the parser made of them. 
+    /// 
+    /// Tokens passed over without a rule or consume (skip_balanced's inner 
+    /// tokens, subexpressions replayed from the memo) have no event; rules 
+    /// a BacktrackingParser abandoned leave none either. 
+    pub fn parse_with_events(&mut self) -> EventedParse<T> {
+        self.events = Some(Vec::new());
+        let res = self.parse();
+        let events = self.events.take().unwrap_or_default();
+        (res, events.into_iter().map(|(event, _)| event).collect())
+    }
     
     fn parse_sequence(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>>{
         <Self as Parser<T>>::parse_sequence(self, prec_level, sep, end_token)
     }
 
+    fn parse_sequence_bounded(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>, max: usize) -> Vec<Result<Node<T>, ParseError<T>>>{
+        <Self as Parser<T>>::parse_sequence_bounded(self, prec_level, sep, end_token, max)
+    }
+
+    fn parse_sequence_all(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>>{
+        <Self as Parser<T>>::parse_sequence_all(self, prec_level, sep, end_token)
+    }
+
     fn next_binds_tighter_than(&mut self, rbp: PrecedenceLevel) -> bool {
         <Self as Parser<T>>::next_binds_tighter_than(self, rbp)
     }
 
-    fn consume(&mut self, end_token: T) -> Result<(), ParseError<T>> {
+    ///Consumes the next token if it equals `end_token`, else fails with 
+    /// ConsumeFailed (UnexpectedEof at the end of the input). The same as 
+    /// Parser::consume. 
+    pub fn consume(&mut self, end_token: T) -> Result<(), ParseError<T>> {
         <Self as Parser<T>>::consume(self, end_token)
     }
-}
 
-impl<T: Token + Send + Sync + 'static, L: Lexer<T>> Parser<T> for GeneralParser<T, L> {
-    fn parse(&mut self) -> Result<Node<T>, ParseError<T>> {
-        self.parse_expr(PrecedenceLevel::Root)
+    fn consume_one_of(&mut self, tokens: &[T]) -> Result<T, ParseError<T>> {
+        <Self as Parser<T>>::consume_one_of(self, tokens)
     }
 
-    fn parse_expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
-        if let Some(tk) = self.lexer.peek() {
-            self.lexer.next_token();
-            let (lbp, func) = {
-                let val = self.null_map.get(&discriminant(&tk));
-                match val {
-                    Some(val) => val.clone(), 
-                    None => return Err(ParseError::MissingRule {token: tk.clone(), ty: "Null".into()})
-                }
-            };
-            let mut left = func(self, tk, lbp)?;
-            while self.next_binds_tighter_than(rbp) {
-                let tk = self.lexer.next_token(); //implied that token exists
-                let val = {
-                    let v = self.left_map.get(&discriminant(&tk));
-                    match v {
-                        Some(val) => val.clone(), 
-                        None => return Err(ParseError::MissingRule {token: tk.clone(), ty: "Left".into()})
-                    }
-                };
-                let (lbp, _, func) = val;
-                left = func(self, tk, lbp, left)?;
-            }
-            Ok(left)
-        } else {
-            Err(ParseError::Incomplete)
-        }
+    fn consume_if(&mut self, matcher: &dyn TokenMatcher<T>) -> Result<T, ParseError<T>> {
+        <Self as Parser<T>>::consume_if(self, matcher)
     }
 
-    fn parse_sequence(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>>{
+    fn parse_optional(&mut self, rbp: PrecedenceLevel, terminators: &[T]) -> Result<Option<Node<T>>, ParseError<T>> {
+        <Self as Parser<T>>::parse_optional(self, rbp, terminators)
+    }
+
+    fn parse_repeated(&mut self, rbp: PrecedenceLevel, stop: &T) -> Result<Vec<Node<T>>, ParseError<T>> {
+        <Self as Parser<T>>::parse_repeated(self, rbp, stop)
+    }
+
+    fn peek(&self) -> Option<T> {
+        <Self as Parser<T>>::peek(self)
+    }
+
+    fn at_end(&self) -> bool {
+        <Self as Parser<T>>::at_end(self)
+    }
+
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), ParseError<T>> {
+        <Self as Parser<T>>::splice(self, tokens)
+    }
+
+    fn parse_args(&mut self, open: T, sep: T, close: T, item_bp: PrecedenceLevel) -> Result<Vec<Node<T>>, ParseError<T>> {
+        <Self as Parser<T>>::parse_args(self, open, sep, close, item_bp)
+    }
+
+    fn parse_n(&mut self, rbp: PrecedenceLevel, n: usize) -> Result<Vec<Node<T>>, ParseError<T>> {
+        <Self as Parser<T>>::parse_n(self, rbp, n)
+    }
+
+    fn skip_balanced(&mut self, open: T, close: T) -> Result<Vec<T>, ParseError<T>> {
+        <Self as Parser<T>>::skip_balanced(self, open, close)
+    }
+
+    fn consume_matching(&mut self, open: &T) -> Result<T, ParseError<T>> {
+        <Self as Parser<T>>::consume_matching(self, open)
+    }
+
+    fn parse_expr_with_floor(&mut self, rbp: PrecedenceLevel, stop_tokens: &[T]) -> Result<Node<T>, ParseError<T>> {
+        <Self as Parser<T>>::parse_expr_with_floor(self, rbp, stop_tokens)
+    }
+
+    fn parse_expr_right(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        <Self as Parser<T>>::parse_expr_right(self, level)
+    }
+
+    fn parse_expr_left(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        <Self as Parser<T>>::parse_expr_left(self, level)
+    }
+
+    ///Shared body of parse_sequence, parse_sequence_bounded and 
+    /// parse_sequence_all (`collect_all`). 
+    fn sequence(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>, max: Option<usize>, collect_all: bool) -> Vec<Result<Node<T>, ParseError<T>>>{
         let mut results = Vec::new();
         loop {
-            let res = self.parse_expr(prec_level);
-            if res.is_ok() {
-                match &sep {
-                    &Some(ref sep) => {
-                        match self.consume(sep.clone()) {
-                            Ok(()) => {},  
-                            Err(ParseError::ConsumeFailed{expected: _, ref found}) => {
-                                match &end_token {
-                                    &Some(ref end_token) => {
-                                        if end_token == found {
-                                            match self.consume(found.clone()) {
-                                                Ok(()) => break,
-                                                Err(pe) => {
-                                                    results.push(Err(pe));
-                                                }
-                                            }
-                                        } else {
-                                            results.push(Err(ParseError::ConsumeFailed{expected: sep.clone(), found: found.clone()}));
-                                        }
-                                    }, 
-                                    &None => {
-                                        results.push(Err(ParseError::ConsumeFailed{expected: sep.clone(), found: found.clone()}));
-                                    }
-                                };
-                                break
-                            }, 
-                            Err(pe) => results.push(Err(pe))
-                        }
-                    }, 
-                    None => {},
-                }
-            } else {
-                match (&res, end_token) {
-                    (&Err(ParseError::Incomplete), None) => {
-                        return results;
-                    }, 
-                    _ => {}
+            if let Some(limit) = max {
+                let more = match (self.peek_token(), &end_token) {
+                    (Some(tk), Some(end_token)) => tk != end_token, 
+                    (next, _) => next.is_some()
                 };
+                if results.len() >= limit && more {
+                    results.push(Err(ParseError::SequenceTooLong{limit}));
+                    break
+                }
+            }
+            if self.peek_token().is_none() {
+                //Running out is how a sequence without an end token ends
+                if end_token.is_some() {
+                    results.push(Err(ParseError::UnexpectedEof));
+                }
+                break
+            }
+            let before = self.progress_mark();
+            let res = self.parse_expr(prec_level)
+                .and_then(|node| self.check_progress(before).map(|_| node));
+            if res.is_err() {
                 results.push(res);
+                if collect_all && self.resync(&sep, &end_token, &mut results) {
+                    continue
+                }
                 break
             }
             results.push(res);
+            if let Some(ref sep) = sep {
+                match self.consume(sep.clone()) {
+                    Ok(()) => {},  
+                    Err(ParseError::ConsumeFailed{expected: _, ref found}) => {
+                        match end_token {
+                            Some(ref end_token) if end_token == found => {
+                                if let Err(pe) = self.consume(found.clone()) {
+                                    results.push(Err(pe));
+                                }
+                            }, 
+                            _ => {
+                                results.push(Err(ParseError::ConsumeFailed{expected: sep.clone(), found: found.clone()}));
+                                if collect_all && self.resync(&Some(sep.clone()), &end_token, &mut results) {
+                                    continue
+                                }
+                            }
+                        };
+                        break
+                    }, 
+                    Err(pe) => {
+                        //UnexpectedEof: fine after an item unless an end token was due
+                        if end_token.is_some() {
+                            results.push(Err(pe));
+                        }
+                        break
+                    }
+                }
+            }
         };
         results
     }
 
-    fn next_binds_tighter_than(&mut self, rbp: PrecedenceLevel) -> bool {
-        if let Some(tk) = self.lexer.peek() {
-            if let Some((_, next_rbp, _)) = self.left_map.get(&discriminant(&tk)) {
-                *next_rbp > rbp
-            } else {
-                false
+    ///Skips to the next separator or end token after an error in 
+    /// parse_sequence_all, consuming it. Returns whether a separator was 
+    /// found, i.e. whether the sequence goes on. 
+    fn resync(&mut self, sep: &Option<T>, end_token: &Option<T>, results: &mut Vec<Result<Node<T>, ParseError<T>>>) -> bool {
+        loop {
+            let (at_sep, at_end) = match self.peek_token() {
+                Some(tk) => (sep.as_ref() == Some(tk), end_token.as_ref() == Some(tk)), 
+                None => {
+                    if end_token.is_some() {
+                        results.push(Err(ParseError::UnexpectedEof));
+                    }
+                    return false
+                }
+            };
+            self.advance();
+            if at_sep || at_end {
+                return at_sep
             }
-        } else {
-            false
         }
     }
 
-    fn consume(&mut self, end_token: T) -> Result<(), ParseError<T>> {
-        if let Some(tk) = self.lexer.peek() {
-            if tk == end_token {
-                self.lexer.next_token();
-                Ok(())
-            } else {
-                Err(ParseError::ConsumeFailed{expected: end_token, found: tk.clone()})
+    ///Reports an event to the trace hook. The event is only built if there 
+    /// is a hook to receive it. 
+    fn trace(&mut self, event: impl FnOnce() -> TraceEvent<T>) {
+        if let Some(ref mut hook) = self.trace {
+            hook(event());
+        }
+    }
+
+    ///Body of parse_expr, once it's known there is a token to start from. 
+    fn expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        if let Some(tk) = self.lookahead().filter(|tk| self.spec.is_closing(tk)) {
+            return Err(ParseError::UnexpectedClosing{token: tk.clone()})
+        }
+        let start = self.lexer.position();
+        let start_span = if self.spanned { self.lexer.current_span() } else { None };
+        let mut left = match self.take_reused(start_span, rbp) {
+            Some(node) => node, 
+            None => self.null_expr(start, start_span)?
+        };
+        //Single map lookup per operator: the rule found while checking the
+        // binding power is the one that gets executed. 
+        while let Some(info) = self.next_left_info(rbp) {
+            let start = self.lexer.position();
+            let mark = self.event_mark();
+            let tk = self.advance_recorded(TokenRole::Operator); //implied that token exists
+            let fallbacks = self.left_fallbacks(&tk, &info);
+            //Only backtracking needs the left node more than once
+            let retry = if fallbacks.is_empty() { None } else { Some(left.clone()) };
+            //The level the rule parses its right operand at, as registered 
+            // for this token: one below its binding power if it is right 
+            // associative, so tokens sharing a level can associate differently. 
+            let (operand_bp, _, func) = info;
+            log_trace!("left rule for {} at {}", tk, operand_bp);
+            self.trace(|| TraceEvent::LeftRule{token: tk.clone()});
+            let mut res = func(self, tk, operand_bp, left);
+            if let Some(left) = retry {
+                for (operand_bp, _, func) in fallbacks {
+                    if res.is_ok() || !self.rewind_to(start) {
+                        break
+                    }
+                    self.rewind_events(mark);
+                    let tk = self.advance_recorded(TokenRole::Operator);
+                    log_trace!("left rule alternative for {} at {}", tk, operand_bp);
+                    self.trace(|| TraceEvent::LeftRule{token: tk.clone()});
+                    res = func(self, tk, operand_bp, left.clone());
+                }
+            }
+            left = self.recover(res)?;
+            left = self.span_from(start_span, left);
+        }
+        Ok(left)
+    }
+
+    ///The null rule half of expr: runs the rule of the token the expression 
+    /// starts with, which is at `start`. 
+    fn null_expr(&mut self, start: Option<usize>, start_span: Option<Span>) -> Result<Node<T>, ParseError<T>> {
+        let mark = self.event_mark();
+        let tk = self.advance_recorded(TokenRole::Operand);
+        let info = self.spec.null_rule(&tk)
+            .or_else(|| self.null_alternatives.get(&self.spec.key_fn().key(&tk)).and_then(|rules| rules.first()))
+            .or(self.spec.default_rules().0)
+            .cloned();
+        let lexical = self.spec.is_error_token(&tk);
+        let fallbacks = if lexical { Vec::new() } else { self.null_fallbacks(&tk, info.as_ref()) };
+        let opening = self.spec.is_opening(&tk);
+        if opening {
+            if let Some(limit) = self.spec.max_nesting().filter(|&limit| self.nesting >= limit) {
+                return Err(ParseError::NestingTooDeep{limit})
+            }
+            self.nesting += 1;
+        }
+        //Brackets start afresh: stops don't apply inside them
+        let stops = if opening { ::std::mem::take(&mut self.stops) } else { Vec::new() };
+        let mut res = match info {
+            _ if lexical => Err(ParseError::LexicalError{token: tk}), 
+            Some((_, rbp, func)) => {
+                log_trace!("null rule for {} at {}", tk, rbp);
+                self.trace(|| TraceEvent::NullRule{token: tk.clone()});
+                func(self, tk, rbp)
+            }, 
+            None => Err(ParseError::MissingRule {token: tk, ty: "Null".into()})
+        };
+        for (_, rbp, func) in fallbacks {
+            if res.is_ok() || !self.rewind_to(start) {
+                break
+            }
+            self.rewind_events(mark);
+            let tk = self.advance_recorded(TokenRole::Operand);
+            log_trace!("null rule alternative for {} at {}", tk, rbp);
+            self.trace(|| TraceEvent::NullRule{token: tk.clone()});
+            res = func(self, tk, rbp);
+        }
+        if opening {
+            self.nesting -= 1;
+            self.stops = stops;
+        }
+        self.classify_null(mark);
+        let left = self.recover(res)?;
+        Ok(self.span_from(start_span, left))
+    }
+
+    ///The old subtree reparse found at `span`, with the lexer moved past the 
+    /// tokens it covers. Like the memo, nothing is reused under 
+    /// parse_expr_with_floor's stops. 
+    fn take_reused(&mut self, span: Option<Span>, rbp: PrecedenceLevel) -> Option<Node<T>> {
+        if self.reuse.is_empty() || !self.stops.is_empty() {
+            return None
+        }
+        let (node, end, group) = self.reuse.remove(&span?.start)?;
+        //Outside brackets it may be a left rule's result, which parsing only 
+        // builds again if its operator binds here 
+        if !group && node.token().and_then(|tk| self.left_rule_for(tk)).is_some_and(|info| info.1 <= rbp) {
+            return None
+        }
+        while self.lexer.current_span().is_some_and(|span| span.start < end) {
+            self.advance();
+        }
+        self.reused += 1;
+        Some(node)
+    }
+
+    ///In spanned mode, gives a Composite `node` the span running from 
+    /// `start` to the last token consumed, replacing the narrower one it may 
+    /// already have. Simple and Error nodes have nowhere to keep a span. 
+    fn span_from(&self, start: Option<Span>, node: Node<T>) -> Node<T> {
+        match (node, start, self.last_span) {
+            (Node::Composite{token, children, ..}, Some(start), Some(last)) => {
+                Node::Composite{token, children, span: Some(Span{end: last.end, ..start})}
+            }, 
+            (node, _, _) => node
+        }
+    }
+
+    ///The null alternatives of `tk` still to try if `used` fails. 
+    fn null_fallbacks(&self, tk: &T, used: Option<&NullInfo<T>>) -> Vec<NullInfo<T>> {
+        if self.null_alternatives.is_empty() {
+            return Vec::new()
+        }
+        match self.null_alternatives.get(&self.spec.key_fn().key(tk)) {
+            Some(rules) => rules.iter()
+                .filter(|rule| used.is_none_or(|used| !Arc::ptr_eq(&rule.2, &used.2)))
+                .cloned()
+                .collect(), 
+            None => Vec::new()
+        }
+    }
+
+    ///The left alternatives of `tk` still to try if `used` fails. 
+    fn left_fallbacks(&self, tk: &T, used: &LeftInfo<T>) -> Vec<LeftInfo<T>> {
+        if self.left_alternatives.is_empty() {
+            return Vec::new()
+        }
+        match self.left_alternatives.get(&self.spec.key_fn().key(tk)) {
+            Some(rules) => rules.iter()
+                .filter(|rule| !Arc::ptr_eq(&rule.2, &used.2))
+                .cloned()
+                .collect(), 
+            None => Vec::new()
+        }
+    }
+
+    ///The memoized result of parse_expr(rbp) at the current position, with 
+    /// the lexer moved past the tokens it covers. 
+    /// Expressions parsed under parse_expr_with_floor's stops end early, so 
+    /// they are neither looked up nor stored. 
+    fn memo_lookup(&mut self, rbp: PrecedenceLevel) -> Option<Result<Node<T>, ParseError<T>>> {
+        if !self.stops.is_empty() {
+            return None
+        }
+        let start = self.lexer.position()?;
+        let (res, end) = self.memo.as_ref()?.get(&(start, rbp)).cloned()?;
+        while self.lexer.position().is_some_and(|position| position < end) {
+            self.advance();
+        }
+        Some(res)
+    }
+
+    fn memo_store(&mut self, start: Option<usize>, rbp: PrecedenceLevel, res: &Result<Node<T>, ParseError<T>>) {
+        if !self.stops.is_empty() {
+            return
+        }
+        if let (Some(memo), Some(start), Some(end)) = (self.memo.as_mut(), start, self.lexer.position()) {
+            memo.insert((start, rbp), (res.clone(), end));
+        }
+    }
+
+    ///Steps the lexer back to position `start`, for retrying a token with its 
+    /// next alternative. False if the lexer can't be rewound (a plain 
+    /// GeneralParser, or a lexer without positions). 
+    fn rewind_to(&mut self, start: Option<usize>) -> bool {
+        let (rewind, start) = match (self.rewind, start) {
+            (Some(rewind), Some(start)) => (rewind, start), 
+            _ => return false
+        };
+        self.peeked = None;
+        self.last_taken = None;
+        while self.lexer.position().is_some_and(|position| position > start) {
+            if !rewind(&mut self.lexer) {
+                return false
             }
+        }
+        self.lexer.position() == Some(start)
+    }
+
+    ///Looks up the left rule of the next token, returning it only if that 
+    /// token binds tighter than rbp. Cloning the rule info out of the map 
+    /// only bumps the Arc reference count of the rule. 
+    fn next_left_info(&mut self, rbp: PrecedenceLevel) -> Option<LeftInfo<T>> {
+        self.peek_token()?;
+        let tk = self.lookahead()?;
+        if self.spec.is_closing(tk) || self.spec.is_error_token(tk) || self.stops.contains(tk) {
+            return None
+        }
+        let info = self.left_rule_for(tk)?;
+        log_trace!("{} binds at {} against {}", tk, info.1, rbp);
+        if info.1 > rbp {
+            Some(info)
         } else {
-            Err(ParseError::Incomplete)
+            None
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
-    use lexer::LexerVec;
-    //Catch Send/Sync changes
-    #[test]
-    fn test_parser_send() {
-        fn assert_send<T: Send>() {}
-        assert_send::<GeneralParser<String, LexerVec<String>>>();
+    ///The left rule the parser runs for `tk`, with the binding powers the 
+    /// precedence table (if set) gives it. 
+    fn left_rule_for(&self, tk: &T) -> Option<LeftInfo<T>> {
+        let info = self.spec.left_rule(tk)
+            .or_else(|| self.left_alternatives.get(&self.spec.key_fn().key(tk)).and_then(|rules| rules.first()))
+            .or(self.spec.default_rules().1)?;
+        let (lbp, next_rbp) = match self.precedence_table.as_ref().and_then(|table| table.lbp(tk)) {
+            Some(level) if info.0 < info.1 => (level.prev(), level), 
+            Some(level) => (level, level), 
+            None => (info.0, info.1)
+        };
+        Some((lbp, next_rbp, info.2.clone()))
     }
 
-    #[test]
-    fn test_parser_sync() {
-        fn assert_sync<T: Sync>() {}
-        assert_sync::<GeneralParser<String, LexerVec<String>>>();
+    ///Borrows the next token from the lexer when it can lend one (see 
+    /// Lexer::peek_ref). Otherwise the lexer is peeked at most once per 
+    /// position; repeated peeks (such as each nested parse_expr checking the 
+    /// same operator on the way out) are served from the lookahead slot. 
+    pub(crate) fn peek_token(&mut self) -> Option<&T> {
+        if self.peeked.is_none() && self.lexer.peek_ref().is_none() {
+            self.peeked = Some(self.lexer.peek());
+        }
+        self.lookahead()
+    }
+
+    ///The next token as peek_token left it: borrowed from the lookahead slot, 
+    /// or from the lexer if it lends tokens. Never clones. 
+    fn lookahead(&self) -> Option<&T> {
+        match self.peeked {
+            Some(ref tk) => tk.as_ref(), 
+            None => self.lexer.peek_ref()
+        }
+    }
+
+    ///Where a loop helper's item started, for check_progress. 
+    fn progress_mark(&self) -> ProgressMark {
+        (self.lexer.position(), self.consumed, self.spliced)
+    }
+
+    ///Errors if the item parsed since `mark` took nothing from the input (and 
+    /// a token is left): either the lexer is still where it was, or as many 
+    /// tokens were spliced back as were consumed. Loop helpers call this after 
+    /// each item they parse. 
+    fn check_progress(&mut self, mark: ProgressMark) -> Result<(), ParseError<T>> {
+        let (position, consumed, spliced) = mark;
+        let stuck = position.is_some() && self.lexer.position() == position;
+        let put_back = self.consumed - consumed == self.spliced - spliced;
+        if !stuck && !put_back {
+            return Ok(())
+        }
+        match self.peek_token() {
+            Some(tk) => Err(ParseError::NoProgress{token: tk.clone()}), 
+            None => Ok(())
+        }
+    }
+
+    ///Consumes the statement terminator, if there is one and input is left. 
+    fn end_statement(&mut self) -> Result<(), ParseError<T>> {
+        match self.spec.statement_terminator().cloned() {
+            Some(terminator) if self.peek_token().is_some() => self.consume(terminator), 
+            _ => Ok(())
+        }
+    }
+
+    ///Skips tokens up to and including the next `token`, or to the end. 
+    fn skip_past(&mut self, token: &T) {
+        while let Some(next) = self.peek_token() {
+            let found = next == token;
+            self.advance();
+            if found {
+                break
+            }
+        }
+    }
+
+    ///In recovery mode, turns a recoverable error into an Error node. 
+    fn recover(&self, res: Result<Node<T>, ParseError<T>>) -> Result<Node<T>, ParseError<T>> {
+        if !self.recover_errors {
+            return res
+        }
+        match res {
+            Err(ParseError::MissingRule{token, ty}) => Ok(Node::Error{
+                message: ParseError::MissingRule{token: token.clone(), ty}.to_string(), 
+                recovered_at: Some(token)
+            }), 
+            Err(ParseError::ConsumeFailed{expected, found}) => Ok(Node::Error{
+                message: ParseError::ConsumeFailed{expected, found: found.clone()}.to_string(), 
+                recovered_at: Some(found)
+            }), 
+            Err(ParseError::ConsumeOneFailed{expected, found}) => Ok(Node::Error{
+                message: ParseError::ConsumeOneFailed{expected, found: found.clone()}.to_string(), 
+                recovered_at: Some(found)
+            }), 
+            Err(ParseError::ConsumeIfFailed{found}) => Ok(Node::Error{
+                message: ParseError::ConsumeIfFailed{found: found.clone()}.to_string(), 
+                recovered_at: Some(found)
+            }), 
+            Err(ParseError::LexicalError{token}) => Ok(Node::Error{
+                message: ParseError::LexicalError{token: token.clone()}.to_string(), 
+                recovered_at: Some(token)
+            }), 
+            Err(ParseError::MismatchedDelimiter{open, expected, found}) => Ok(Node::Error{
+                message: ParseError::MismatchedDelimiter{open, expected, found: found.clone()}.to_string(), 
+                recovered_at: Some(found)
+            }), 
+            res => res
+        }
+    }
+
+    ///Advances the lexer, invalidating the lookahead slot. 
+    fn advance(&mut self) -> T {
+        self.peeked = None;
+        self.started = true;
+        self.consumed += 1;
+        if self.spanned {
+            self.last_span = self.lexer.current_span();
+        }
+        let token = self.lexer.next_token();
+        if self.count_kinds {
+            *self.kinds.entry(self.spec.key_fn().key(&token)).or_insert(0) += 1;
+        }
+        self.last_taken = Some(token.clone());
+        token
+    }
+
+    ///advance, recording the token for parse_with_events when it's running. 
+    fn advance_recorded(&mut self, role: TokenRole) -> T {
+        if self.events.is_none() {
+            return self.advance()
+        }
+        let position = self.lexer.position();
+        let span = self.lexer.current_span();
+        let token = self.advance();
+        let depth = self.depth;
+        if let Some(events) = self.events.as_mut() {
+            events.push((ParseEvent{token: token.clone(), role, position, span}, depth));
+        }
+        token
+    }
+
+    ///How many events have been recorded, to rewind to or classify from. 
+    fn event_mark(&self) -> usize {
+        self.events.as_ref().map_or(0, Vec::len)
+    }
+
+    ///Drops the events of a rule that is about to be retried. 
+    fn rewind_events(&mut self, mark: usize) {
+        if let Some(events) = self.events.as_mut() {
+            events.truncate(mark);
+        }
+    }
+
+    ///Once its rule has run, reclassifies the null token recorded at `mark` 
+    /// by what the rule went on to do (see TokenRole). 
+    fn classify_null(&mut self, mark: usize) {
+        let events = match self.events.as_mut() {
+            Some(events) if events.len() > mark => events, 
+            _ => return
+        };
+        let depth = events[mark].1;
+        let rest = &events[mark + 1..];
+        let nested = rest.iter().any(|&(_, d)| d > depth);
+        let delimited = rest.iter().any(|(event, d)| *d == depth && event.role == TokenRole::Delimiter);
+        events[mark].0.role = match (nested, delimited) {
+            (true, true) => TokenRole::Delimiter, 
+            (false, false) => TokenRole::Operand, 
+            _ => TokenRole::Operator
+        };
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, L: Lexer<T>, K: KeyFn<T>, S: BuildHasher> Parser<T> for GeneralParser<T, L, K, S> {
+    ///Parses from the entry level of the spec (see ParserSpec::set_entry), 
+    /// PrecedenceLevel::Root unless the spec says otherwise. 
+    fn parse(&mut self) -> Result<Node<T>, ParseError<T>> {
+        let entry = self.spec.entry();
+        self.parse_expr(entry)
+    }
+
+    ///Returns Incomplete if there is no token to start an expression with, 
+    /// EmptyInput if there never were any tokens, or UnexpectedEof if the 
+    /// input ran out inside an enclosing expression. 
+    fn parse_expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        if self.peek_token().is_none() {
+            return Err(if !self.started {
+                ParseError::EmptyInput
+            } else if self.depth == 0 {
+                ParseError::Incomplete
+            } else {
+                ParseError::UnexpectedEof
+            })
+        }
+        self.trace(|| TraceEvent::EnterExpr{rbp});
+        self.depth += 1;
+        let res = match self.memo_lookup(rbp) {
+            Some(res) => res, 
+            None => {
+                let start = self.lexer.position();
+                let res = self.expr(rbp);
+                self.memo_store(start, rbp, &res);
+                res
+            }
+        };
+        self.depth -= 1;
+        self.trace(|| TraceEvent::Exit{node_summary: match res {
+            Ok(ref node) => node.to_string(), 
+            Err(ref err) => err.to_string()
+        }});
+        res
+    }
+
+    fn parse_sequence(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>>{
+        self.sequence(prec_level, sep, end_token, None, false)
+    }
+
+    fn parse_sequence_bounded(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>, max: usize) -> Vec<Result<Node<T>, ParseError<T>>>{
+        self.sequence(prec_level, sep, end_token, Some(max), false)
+    }
+
+    fn parse_sequence_all(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>>{
+        self.sequence(prec_level, sep, end_token, None, true)
+    }
+
+    fn next_binds_tighter_than(&mut self, rbp: PrecedenceLevel) -> bool {
+        self.next_left_info(rbp).is_some()
+    }
+
+    fn consume(&mut self, end_token: T) -> Result<(), ParseError<T>> {
+        match self.peek_token() {
+            Some(tk) if *tk == end_token => {}, 
+            Some(tk) => return Err(ParseError::ConsumeFailed{expected: end_token, found: tk.clone()}), 
+            None => return Err(ParseError::UnexpectedEof)
+        }
+        let tk = self.advance_recorded(TokenRole::Delimiter);
+        log_trace!("consumed {}", tk);
+        self.trace(|| TraceEvent::Consume{token: tk});
+        Ok(())
+    }
+
+    fn peek(&self) -> Option<T> {
+        match self.peeked {
+            Some(ref tk) => tk.clone(), 
+            None => self.lexer.peek()
+        }
+    }
+
+    fn consume_one_of(&mut self, tokens: &[T]) -> Result<T, ParseError<T>> {
+        match self.peek_token() {
+            Some(tk) if tokens.contains(tk) => {}, 
+            Some(tk) => return Err(ParseError::ConsumeOneFailed{expected: tokens.to_vec(), found: tk.clone()}), 
+            None => return Err(ParseError::UnexpectedEof)
+        }
+        let tk = self.advance_recorded(TokenRole::Delimiter);
+        log_trace!("consumed {}", tk);
+        self.trace(|| TraceEvent::Consume{token: tk.clone()});
+        Ok(tk)
+    }
+
+    fn consume_if(&mut self, matcher: &dyn TokenMatcher<T>) -> Result<T, ParseError<T>> {
+        match self.peek_token() {
+            Some(tk) if matcher.matches(tk) => {}, 
+            Some(tk) => return Err(ParseError::ConsumeIfFailed{found: tk.clone()}), 
+            None => return Err(ParseError::UnexpectedEof)
+        }
+        let tk = self.advance_recorded(TokenRole::Delimiter);
+        log_trace!("consumed {}", tk);
+        self.trace(|| TraceEvent::Consume{token: tk.clone()});
+        Ok(tk)
+    }
+
+    fn parse_optional(&mut self, rbp: PrecedenceLevel, terminators: &[T]) -> Result<Option<Node<T>>, ParseError<T>> {
+        match self.peek_token() {
+            Some(tk) if !terminators.contains(tk) => {}, 
+            _ => return Ok(None)
+        }
+        self.parse_expr(rbp).map(Some)
+    }
+
+    fn parse_repeated(&mut self, rbp: PrecedenceLevel, stop: &T) -> Result<Vec<Node<T>>, ParseError<T>> {
+        let mut nodes = Vec::new();
+        loop {
+            match self.peek_token() {
+                Some(tk) if tk == stop => break, 
+                Some(_) => {}, 
+                None => return Err(ParseError::UnexpectedEof)
+            }
+            //A successful parse_expr consumes at least the token its null rule 
+            // was found for, but a Lexer that doesn't actually move, or a rule 
+            // that splices that token back, would still spin here forever. 
+            let before = self.progress_mark();
+            nodes.push(self.parse_expr(rbp)?);
+            self.check_progress(before)?;
+        }
+        self.advance_recorded(TokenRole::Delimiter);
+        Ok(nodes)
+    }
+
+    fn at_end(&self) -> bool {
+        self.lookahead().is_none() && self.lexer.peek().is_none()
+    }
+
+    ///The lookahead slot holds the token that was next before the splice, so 
+    /// it is dropped, as is the memo table (positions past this point now 
+    /// refer to different tokens). 
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), ParseError<T>> {
+        let count = tokens.len();
+        self.lexer.splice(tokens).map_err(|_| ParseError::SpliceUnsupported)?;
+        self.spliced += count;
+        self.peeked = None;
+        if let Some(memo) = self.memo.as_mut() {
+            memo.clear();
+        }
+        Ok(())
+    }
+
+    fn parse_args(&mut self, open: T, sep: T, close: T, item_bp: PrecedenceLevel) -> Result<Vec<Node<T>>, ParseError<T>> {
+        self.consume(open)?;
+        let mut args = Vec::new();
+        if self.peek_token() == Some(&close) {
+            self.consume(close)?;
+            return Ok(args)
+        }
+        let delimiters = [sep, close];
+        loop {
+            args.push(self.parse_expr(item_bp)?);
+            if self.consume_one_of(&delimiters)? == delimiters[1] {
+                return Ok(args)
+            }
+        }
+    }
+
+    fn parse_n(&mut self, rbp: PrecedenceLevel, n: usize) -> Result<Vec<Node<T>>, ParseError<T>> {
+        let mut nodes = Vec::with_capacity(n);
+        for _ in 0..n {
+            if self.peek_token().is_none() {
+                return Err(ParseError::UnexpectedEof)
+            }
+            nodes.push(self.parse_expr(rbp)?);
+        }
+        Ok(nodes)
+    }
+
+    fn skip_balanced(&mut self, open: T, close: T) -> Result<Vec<T>, ParseError<T>> {
+        self.consume(open.clone())?;
+        let mut inner = Vec::new();
+        let mut depth = 0usize;
+        loop {
+            if self.peek_token().is_none() {
+                return Err(ParseError::Incomplete)
+            }
+            let tk = self.advance();
+            if tk == close {
+                if depth == 0 {
+                    return Ok(inner)
+                }
+                depth -= 1;
+            } else if tk == open {
+                depth += 1;
+            }
+            inner.push(tk);
+        }
+    }
+
+    fn consume_matching(&mut self, open: &T) -> Result<T, ParseError<T>> {
+        let expected = match self.spec.closing_for(open) {
+            Some(close) => close.clone(), 
+            None => return Err(ParseError::MissingRule{token: open.clone(), ty: "Delimiter".into()})
+        };
+        match self.peek_token() {
+            Some(tk) if *tk == expected => {}, 
+            Some(tk) => return Err(ParseError::MismatchedDelimiter{open: open.clone(), expected, found: tk.clone()}), 
+            None => return Err(ParseError::UnexpectedEof)
+        }
+        let tk = self.advance_recorded(TokenRole::Delimiter);
+        log_trace!("consumed {}", tk);
+        self.trace(|| TraceEvent::Consume{token: tk.clone()});
+        Ok(tk)
+    }
+
+    fn parse_expr_with_floor(&mut self, rbp: PrecedenceLevel, stop_tokens: &[T]) -> Result<Node<T>, ParseError<T>> {
+        let saved = self.stops.len();
+        self.stops.extend_from_slice(stop_tokens);
+        let res = self.parse_expr(rbp);
+        self.stops.truncate(saved);
+        res
+    }
+
+    fn parse_expr_right(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        self.parse_expr(level.prev())
+    }
+
+    fn parse_expr_left(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        self.parse_expr(level)
+    }
+}
+
+/// Each call to next() parses one top-level expression, as parse() does. 
+/// The iterator ends when the lexer is exhausted *before* an expression starts; 
+/// running out of tokens partway through one is yielded as 
+/// Some(Err(ParseError::UnexpectedEof)). 
+/// 
+/// An error that consumed nothing, such as UnexpectedClosing for a stray 
+/// closing token, would come back on every call, so the token it is stuck 
+/// on is skipped after it is yielded. So would a rule that splices its own 
+/// token back, which is yielded as ParseError::NoProgress (and skipped) in 
+/// place of the node it returned. 
+impl<T: Token + Send + Sync + 'static, L: Lexer<T>, K: KeyFn<T>, S: BuildHasher> Iterator for GeneralParser<T, L, K, S> {
+    type Item = Result<Node<T>, ParseError<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.peek_token()?;
+        let before = self.progress_mark();
+        let res = self.parse();
+        match self.check_progress(before) {
+            Ok(()) => Some(res), 
+            Err(stuck) => {
+                self.advance();
+                Some(res.and(Err(stuck)))
+            }
+        }
+    }
+}
+
+/// A GeneralParser that can register several rules per token and tries them 
+/// in order (ordered choice): when a rule fails, the lexer is stepped back to 
+/// the token and the next alternative runs, until one succeeds or the last 
+/// one's error is returned. 
+/// 
+/// The rule from the ParserSpec, if the token has one, is always tried 
+/// first, then the alternatives in the order they were added. Once a rule 
+/// succeeds the choice is final, a later failure doesn't come back to it. 
+/// Left alternatives only run for tokens that bind tighter than the current 
+/// rbp, going by the binding power of the first rule. 
+/// 
+/// Rewinding needs a DoubleEndedLexer that reports its position. 
+/// 
+/// **Beware:** nested alternatives that fail late retry the same input over 
+/// and over, which is exponential in the nesting depth. Keep alternatives 
+/// that can fail after consuming much input to a minimum, and turn on 
+/// enable_memoization so each subexpression is only parsed once per level. 
+/// 
+/// Derefs to the GeneralParser inside, for its settings (set_trace, 
+/// set_error_recovery and so on). 
+pub struct BacktrackingParser<T, L, K = DiscriminantKey, S = RandomState>
+    where T: Token + Send + Sync + 'static, 
+          L: DoubleEndedLexer<T>, 
+          K: KeyFn<T>, 
+          S: BuildHasher
+{
+    parser: GeneralParser<T, L, K, S>,
+}
+
+impl<T: Token + Send + Sync + 'static, L: DoubleEndedLexer<T>, K: KeyFn<T>, S: BuildHasher> BacktrackingParser<T, L, K, S> {
+    pub fn new(spec: impl IntoFrozenSpec<T, Key=K, Hasher=S>, lexer: L) -> BacktrackingParser<T, L, K, S> {
+        let mut parser = GeneralParser::new(spec, lexer);
+        parser.rewind = Some(|lexer: &mut L| lexer.prev_token().is_some());
+        BacktrackingParser {
+            parser
+        }
+    }
+
+    ///Adds a null rule for `token` to try after the ones already registered. 
+    pub fn add_null_alternative(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) {
+        let key = self.parser.spec.key_fn().key(&token.into());
+        self.parser.null_alternatives.entry(key).or_default().push((bp, bp, Arc::new(func)));
+    }
+
+    ///Adds a left rule for `token` to try after the ones already registered. 
+    pub fn add_left_alternative(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) {
+        let key = self.parser.spec.key_fn().key(&token.into());
+        self.parser.left_alternatives.entry(key).or_default().push((bp, bp, Arc::new(func)));
+    }
+
+    pub fn into_inner(self) -> GeneralParser<T, L, K, S> {
+        self.parser
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, L: DoubleEndedLexer<T>, K: KeyFn<T>, S: BuildHasher> Deref for BacktrackingParser<T, L, K, S> {
+    type Target = GeneralParser<T, L, K, S>;
+
+    fn deref(&self) -> &GeneralParser<T, L, K, S> {
+        &self.parser
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, L: DoubleEndedLexer<T>, K: KeyFn<T>, S: BuildHasher> DerefMut for BacktrackingParser<T, L, K, S> {
+    fn deref_mut(&mut self) -> &mut GeneralParser<T, L, K, S> {
+        &mut self.parser
+    }
+}
+
+//Rules are handed the GeneralParser inside, which does the backtracking 
+impl<T: Token + Send + Sync + 'static, L: DoubleEndedLexer<T>, K: KeyFn<T>, S: BuildHasher> Parser<T> for BacktrackingParser<T, L, K, S> {
+    fn parse(&mut self) -> Result<Node<T>, ParseError<T>> {
+        Parser::parse(&mut self.parser)
+    }
+
+    fn parse_expr(&mut self, rbp: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        Parser::parse_expr(&mut self.parser, rbp)
+    }
+
+    fn parse_sequence(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>> {
+        Parser::parse_sequence(&mut self.parser, prec_level, sep, end_token)
+    }
+
+    fn parse_sequence_bounded(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>, max: usize) -> Vec<Result<Node<T>, ParseError<T>>> {
+        Parser::parse_sequence_bounded(&mut self.parser, prec_level, sep, end_token, max)
+    }
+
+    fn parse_sequence_all(&mut self, prec_level: PrecedenceLevel, sep: Option<T>, end_token: Option<T>) -> Vec<Result<Node<T>, ParseError<T>>> {
+        Parser::parse_sequence_all(&mut self.parser, prec_level, sep, end_token)
+    }
+
+    fn next_binds_tighter_than(&mut self, rbp: PrecedenceLevel) -> bool {
+        Parser::next_binds_tighter_than(&mut self.parser, rbp)
+    }
+
+    fn consume(&mut self, end_token: T) -> Result<(), ParseError<T>> {
+        Parser::consume(&mut self.parser, end_token)
+    }
+
+    fn peek(&self) -> Option<T> {
+        Parser::peek(&self.parser)
+    }
+
+    fn consume_one_of(&mut self, tokens: &[T]) -> Result<T, ParseError<T>> {
+        Parser::consume_one_of(&mut self.parser, tokens)
+    }
+
+    fn consume_if(&mut self, matcher: &dyn TokenMatcher<T>) -> Result<T, ParseError<T>> {
+        Parser::consume_if(&mut self.parser, matcher)
+    }
+
+    fn parse_optional(&mut self, rbp: PrecedenceLevel, terminators: &[T]) -> Result<Option<Node<T>>, ParseError<T>> {
+        Parser::parse_optional(&mut self.parser, rbp, terminators)
+    }
+
+    fn parse_repeated(&mut self, rbp: PrecedenceLevel, stop: &T) -> Result<Vec<Node<T>>, ParseError<T>> {
+        Parser::parse_repeated(&mut self.parser, rbp, stop)
+    }
+
+    fn at_end(&self) -> bool {
+        Parser::at_end(&self.parser)
+    }
+
+    fn splice(&mut self, tokens: Vec<T>) -> Result<(), ParseError<T>> {
+        Parser::splice(&mut self.parser, tokens)
+    }
+
+    fn parse_args(&mut self, open: T, sep: T, close: T, item_bp: PrecedenceLevel) -> Result<Vec<Node<T>>, ParseError<T>> {
+        Parser::parse_args(&mut self.parser, open, sep, close, item_bp)
+    }
+
+    fn parse_n(&mut self, rbp: PrecedenceLevel, n: usize) -> Result<Vec<Node<T>>, ParseError<T>> {
+        Parser::parse_n(&mut self.parser, rbp, n)
+    }
+
+    fn skip_balanced(&mut self, open: T, close: T) -> Result<Vec<T>, ParseError<T>> {
+        Parser::skip_balanced(&mut self.parser, open, close)
+    }
+
+    fn consume_matching(&mut self, open: &T) -> Result<T, ParseError<T>> {
+        Parser::consume_matching(&mut self.parser, open)
+    }
+
+    fn parse_expr_with_floor(&mut self, rbp: PrecedenceLevel, stop_tokens: &[T]) -> Result<Node<T>, ParseError<T>> {
+        Parser::parse_expr_with_floor(&mut self.parser, rbp, stop_tokens)
+    }
+
+    fn parse_expr_right(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        Parser::parse_expr_right(&mut self.parser, level)
+    }
+
+    fn parse_expr_left(&mut self, level: PrecedenceLevel) -> Result<Node<T>, ParseError<T>> {
+        Parser::parse_expr_left(&mut self.parser, level)
+    }
+}
+
+//An edit as reparse sees it: the changed byte range of the new source, and 
+// how far the text after it moved. 
+struct Edit {
+    changed: Range<usize>, 
+    shift: isize, 
+    //The new input's token spans, in order
+    spans: Vec<Span>, 
+    //For each of those tokens that opens a bracket, the token closing it
+    closers: Vec<Option<usize>>
+}
+
+impl Edit {
+    //Whether new-source bytes start..end are clear of the edit. 
+    fn clear(&self, start: usize, end: usize) -> bool {
+        end <= self.changed.start || start >= self.changed.end
+    }
+
+    //How far an old span moved, if it lies clear of the edit. 
+    fn shift_of(&self, span: Span) -> Option<isize> {
+        if span.end <= self.changed.start {
+            Some(0)
+        } else if span.start as isize + self.shift >= self.changed.end as isize {
+            Some(self.shift)
+        } else {
+            None
+        }
+    }
+
+    //Gathers the null rule results in `node` that reparse can reuse. 
+    fn collect<T: Token>(&self, node: &Node<T>, reuse: &mut Reusable<T>) {
+        let mut inner = Vec::new();
+        spanned_children(node, &mut inner);
+        if let Some(span) = node.span() {
+            //A left rule's result holds its left operand, which starts 
+            // where it does; a null rule's starts after its token. 
+            let null_result = !inner.iter().any(|child| child.span().is_some_and(|child| child.start == span.start));
+            let fit = self.shift_of(span).and_then(|shift| self.fits(span, shift).map(|tokens| (shift, tokens)));
+            if let (true, Some((shift, tokens))) = (null_result, fit) {
+                let node = if span.end <= self.changed.start { node.clone() } else { rebase(node.clone(), shift, &self.spans) };
+                let group = self.closers[tokens.start] == Some(tokens.end - 1);
+                reuse.insert(moved(span.start, shift), (node, moved(span.end, shift), group));
+                return
+            }
+        }
+        for child in inner {
+            self.collect(child, reuse);
+        }
+    }
+
+    //The new input's tokens the old subtree at `span` covers once moved by 
+    // `shift`, if it covers whole ones and the token after it is clear of 
+    // the edit too. Tokens the edit merged with their neighbours fail the 
+    // first check. 
+    fn fits(&self, span: Span, shift: isize) -> Option<Range<usize>> {
+        let spans = &self.spans;
+        let (start, end) = (moved(span.start, shift), moved(span.end, shift));
+        let first = spans.binary_search_by_key(&start, |span| span.start).ok()?;
+        let next = spans.partition_point(|span| span.start < end);
+        if next <= first || spans[next - 1].end != end {
+            return None
+        }
+        let clear = match spans.get(next) {
+            Some(span) => self.clear(span.start, span.end), 
+            //The end of the input only stays put after the edit
+            None => span.end > self.changed.start
+        };
+        if clear { Some(first..next) } else { None }
+    }
+}
+
+fn moved(offset: usize, shift: isize) -> usize {
+    (offset as isize + shift) as usize
+}
+
+//The nodes with a span below `node` that aren't nested in other ones. 
+fn spanned_children<'a, T: Token>(node: &'a Node<T>, out: &mut Vec<&'a Node<T>>) {
+    for child in node.children() {
+        if child.span().is_some() {
+            out.push(child);
+        } else {
+            spanned_children(child, out);
+        }
+    }
+}
+
+//Moves the spans in `node` by `shift`, taking lines and columns from the 
+// new input's tokens. 
+fn rebase<T: Token>(node: Node<T>, shift: isize, spans: &[Span]) -> Node<T> {
+    match node {
+        Node::Composite{token, children, span} => Node::Composite{
+            token, 
+            children: children.into_iter().map(|child| rebase(child, shift, spans)).collect(), 
+            span: span.map(|span| {
+                let (start, end) = (moved(span.start, shift), moved(span.end, shift));
+                let (line, column) = match spans.binary_search_by_key(&start, |span| span.start) {
+                    Ok(i) => (spans[i].line, spans[i].column), 
+                    Err(_) => (span.line, span.column)
+                };
+                Span{start, end, line, column}
+            })
+        }, 
+        node => node
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fmt::{Display, Error, Formatter};
+    use std::mem::discriminant;
+
+    use super::*;
+    use lexer::LexerVec;
+
+    #[derive(Clone, Debug, Eq, Hash, PartialEq)]
+    enum TestToken {
+        Ident(String), 
+        Add, 
+        Mul, 
+        Semi, 
+        //What a lexer emits for a char it doesn't know
+        Unknown(char), 
+    }
+
+    impl Display for TestToken {
+        fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+            match *self {
+                TestToken::Ident(ref s) => write!(f, "{}", s), 
+                TestToken::Add => write!(f, "+"), 
+                TestToken::Mul => write!(f, "*"), 
+                TestToken::Semi => write!(f, ";"), 
+                TestToken::Unknown(c) => write!(f, "{}", c), 
+            }
+        }
+    }
+
+    fn arith_spec() -> ParserSpec<TestToken> {
+        let mut spec = ParserSpec::new();
+        spec.add_null_assoc(TestToken::Ident("".into()), PrecedenceLevel::Root, |_, token, _| {
+            Ok(Node::Simple(token))
+        }).unwrap();
+        spec.add_left_assoc(TestToken::Add, PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec.add_left_assoc(TestToken::Mul, PrecedenceLevel::Second, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec
+    }
+
+    //One token per non-whitespace char: letters are identifiers. 
+    fn lex(src: &str) -> LexerVec<TestToken> {
+        LexerVec::new(src.chars().filter(|c| !c.is_whitespace()).map(|c| match c {
+            '+' => TestToken::Add, 
+            '*' => TestToken::Mul, 
+            ';' => TestToken::Semi, 
+            '?' => TestToken::Unknown('?'), 
+            c => TestToken::Ident(c.to_string()), 
+        }))
+    }
+
+    fn ident(name: &str) -> Node<TestToken> {
+        Node::Simple(TestToken::Ident(name.into()))
+    }
+
+    fn binary(token: TestToken, lhs: Node<TestToken>, rhs: Node<TestToken>) -> Node<TestToken> {
+        Node::Composite{token, children: vec![lhs, rhs], span: None}
+    }
+
+    #[test]
+    fn test_parse_program_terminated_statements() {
+        let mut spec = arith_spec();
+        spec.set_statement_terminator(TestToken::Semi);
+        let mut parser = GeneralParser::new(spec, lex("a; b; c;"));
+        let statements: Result<Vec<_>, _> = parser.parse_program().into_iter().collect();
+        assert_eq!(statements.unwrap(), vec![ident("a"), ident("b"), ident("c")]);
+    }
+
+    #[test]
+    fn test_parse_program_resumes_after_terminator() {
+        let mut spec = arith_spec();
+        spec.set_statement_terminator(TestToken::Semi);
+        let mut parser = GeneralParser::new(spec, lex("a+b; c d; e"));
+        let statements = parser.parse_program();
+        assert_eq!(statements, vec![
+            Ok(binary(TestToken::Add, ident("a"), ident("b"))), 
+            Err(ParseError::ConsumeFailed{expected: TestToken::Semi, found: TestToken::Ident("d".into())}), 
+            Ok(ident("e")), 
+        ]);
+    }
+
+    #[test]
+    fn test_parse_program_failure_that_took_the_terminator() {
+        //The null step takes `;` before failing on it; b must not be skipped
+        let mut spec = arith_spec();
+        spec.set_statement_terminator(TestToken::Semi);
+        let spec = spec.freeze();
+        let mut parser = GeneralParser::new(spec.clone(), lex("a + ; b ; c"));
+        assert_eq!(parser.parse_program(), vec![
+            Err(ParseError::MissingRule{token: TestToken::Semi, ty: "Null".into()}), 
+            Ok(ident("b")), 
+            Ok(ident("c")), 
+        ]);
+
+        let mut parser = GeneralParser::new(spec, lex("a + ; b ; c"));
+        assert_eq!(parser.parse_program_strict(), 
+            Err(vec![ParseError::MissingRule{token: TestToken::Semi, ty: "Null".into()}]));
+    }
+
+    #[test]
+    fn test_parse_expr_right_and_left_grouping() {
+        //Both registered left associative; the rules pick the grouping
+        let mut spec = ParserSpec::new();
+        spec.add_null_assoc(TestToken::Ident("".into()), PrecedenceLevel::Root, |_, token, _| {
+            Ok(Node::Simple(token))
+        }).unwrap();
+        spec.add_left_assoc(TestToken::Add, PrecedenceLevel::First, |parser, token, _, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr_right(PrecedenceLevel::First)?], span: None})
+        }).unwrap();
+        spec.add_left_assoc(TestToken::Mul, PrecedenceLevel::Second, |parser, token, _, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr_left(PrecedenceLevel::Second)?], span: None})
+        }).unwrap();
+        let spec = spec.freeze();
+
+        let mut parser = GeneralParser::new(spec.clone(), lex("a+b+c"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, ident("a"), 
+            binary(TestToken::Add, ident("b"), ident("c")))));
+
+        let mut parser = GeneralParser::new(spec.clone(), lex("a*b*c"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Mul, 
+            binary(TestToken::Mul, ident("a"), ident("b")), ident("c"))));
+
+        //Precedence is unaffected: Mul still binds inside Add's operand
+        let mut parser = GeneralParser::new(spec, lex("a+b*c+d"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, ident("a"), 
+            binary(TestToken::Add, binary(TestToken::Mul, ident("b"), ident("c")), ident("d")))));
+    }
+
+    #[test]
+    fn test_parse_one_reports_position() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a * b c"));
+        assert_eq!(parser.parse_one(), (Ok(binary(TestToken::Mul, ident("a"), ident("b"))), 3));
+        assert_eq!(parser.parse_one(), (Ok(ident("c")), 4));
+        assert_eq!(parser.parse_one(), (Err(ParseError::Incomplete), 4));
+    }
+
+    #[test]
+    fn test_parse_program_strict() {
+        let mut spec = arith_spec();
+        spec.set_statement_terminator(TestToken::Semi);
+        let spec = spec.freeze();
+
+        let mut parser = GeneralParser::new(spec.clone(), lex("a+b; c; d"));
+        assert_eq!(parser.parse_program_strict(), Ok(vec![binary(TestToken::Add, ident("a"), ident("b")), ident("c"), ident("d")]));
+
+        let mut parser = GeneralParser::new(spec, lex("a+b; c d; e; f +"));
+        assert_eq!(parser.parse_program_strict(), Err(vec![
+            ParseError::ConsumeFailed{expected: TestToken::Semi, found: TestToken::Ident("d".into())}, 
+            ParseError::UnexpectedEof, 
+        ]));
+    }
+
+    #[test]
+    fn test_parse_with_events_roles() {
+        let role = |events: &[ParseEvent<TestToken>]| events.iter()
+            .map(|event| (event.token.to_string(), event.role, event.position))
+            .collect::<Vec<_>>();
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b"));
+        let (res, events) = parser.parse_with_events();
+        assert_eq!(res, Ok(binary(TestToken::Add, ident("a"), ident("b"))));
+        assert_eq!(role(&events), vec![
+            ("a".to_string(), TokenRole::Operand, Some(0)), 
+            ("+".to_string(), TokenRole::Operator, Some(1)), 
+            ("b".to_string(), TokenRole::Operand, Some(2)), 
+        ]);
+
+        let mut parser = GeneralParser::new(bracket_spec(), lex("(a) * b"));
+        let (_, events) = parser.parse_with_events();
+        assert_eq!(events.iter().map(|event| event.role).collect::<Vec<_>>(), vec![
+            TokenRole::Delimiter, TokenRole::Operand, TokenRole::Delimiter, TokenRole::Operator, TokenRole::Operand
+        ]);
+        //Not collected outside parse_with_events
+        assert!(parser.events.is_none());
+    }
+
+    #[test]
+    fn test_truncated_input_never_panics() {
+        type Entry = fn(&mut GeneralParser<TestToken, LexerVec<TestToken>>) -> bool;
+        let paren = |s: &str| TestToken::Ident(s.into());
+        let entries: Vec<(&str, Entry)> = vec![
+            ("parse", |p| p.parse().is_ok()), 
+            ("parse_expr", |p| p.parse_expr(PrecedenceLevel::Root).is_ok()), 
+            ("parse_from", |p| p.parse_from(PrecedenceLevel::Root).is_ok()), 
+            ("parse_expression", |p| p.parse_expression(PrecedenceLevel::Root).is_ok()), 
+            ("parse_stream", |p| p.parse_stream().all(|res| res.is_ok())), 
+            ("parse_program", |p| p.parse_program().iter().all(Result::is_ok)), 
+            ("parse_with_events", |p| p.parse_with_events().0.is_ok()), 
+            ("parse_sequence", |p| p.parse_sequence(PrecedenceLevel::Root, Some(TestToken::Semi), Some(TestToken::Ident(")".into()))).iter().all(Result::is_ok)), 
+            ("parse_sequence_bounded", |p| p.parse_sequence_bounded(PrecedenceLevel::Root, Some(TestToken::Semi), None, 1).iter().all(Result::is_ok)), 
+            ("parse_sequence_all", |p| p.parse_sequence_all(PrecedenceLevel::Root, Some(TestToken::Semi), Some(TestToken::Ident(")".into()))).iter().all(Result::is_ok)), 
+            ("next_binds_tighter_than", |p| p.next_binds_tighter_than(PrecedenceLevel::Root)), 
+            ("consume", |p| p.consume(TestToken::Ident("(".into())).is_ok()), 
+            ("consume_one_of", |p| p.consume_one_of(&[TestToken::Add, TestToken::Mul]).is_ok()), 
+            ("consume_if", |p| p.consume_if(&::token::Exact(TestToken::Semi)).is_ok()), 
+            ("parse_optional", |p| p.parse_optional(PrecedenceLevel::Root, &[TestToken::Semi]).is_ok()), 
+            ("parse_repeated", |p| p.parse_repeated(PrecedenceLevel::Root, &TestToken::Ident(")".into())).is_ok()), 
+            ("at_end", |p| p.at_end()), 
+            ("parse_args", |p| p.parse_args(TestToken::Ident("(".into()), TestToken::Semi, TestToken::Ident(")".into()), PrecedenceLevel::Root).is_ok()), 
+            ("parse_n", |p| p.parse_n(PrecedenceLevel::Root, 3).is_ok()), 
+            ("skip_balanced", |p| p.skip_balanced(TestToken::Ident("(".into()), TestToken::Ident(")".into())).is_ok()), 
+            ("consume_matching", |p| p.consume_matching(&TestToken::Ident("(".into())).is_ok()), 
+            ("parse_expr_with_floor", |p| p.parse_expr_with_floor(PrecedenceLevel::Root, &[TestToken::Semi]).is_ok()), 
+            ("parse_expr_right", |p| p.parse_expr_right(PrecedenceLevel::Root).is_ok()), 
+            ("parse_expr_left", |p| p.parse_expr_left(PrecedenceLevel::Root).is_ok()), 
+            ("parse_one", |p| p.parse_one().0.is_ok()), 
+            ("parse_program_strict", |p| p.parse_program_strict().is_ok()), 
+            ("iterator", |p| p.all(|res| res.is_ok())), 
+            ("fork", |p| p.fork().parse().is_ok()), 
+            ("splice", |p| p.splice(vec![TestToken::Add]).is_ok() && p.parse().is_ok()), 
+            ("peek", |p| p.peek().is_some()), 
+        ];
+        for src in &["", "a +", "a *", "(", "(a", "(a +", "a + (", ")", "(b ;", "((a)"] {
+            for &(name, entry) in &entries {
+                let mut spec = bracket_spec();
+                spec.set_statement_terminator(TestToken::Semi);
+                let mut parser = GeneralParser::new(spec, lex(src));
+                //Nothing here is a complete expression
+                if entry(&mut parser) && name.starts_with("parse_e") {
+                    panic!("{} accepted {:?}", name, src);
+                }
+            }
+            let mut parser = BacktrackingParser::new(bracket_spec(), lex(src));
+            parser.add_null_alternative(paren("("), PrecedenceLevel::Root, |parser, _, _| {
+                let inner = parser.parse()?;
+                parser.consume(TestToken::Ident("]".into()))?;
+                Ok(inner)
+            });
+            assert!(parser.parse().is_err(), "{:?}", src);
+
+            let old_tree = GeneralParser::new(bracket_spec(), lex_spans("(a) + b")).parse().unwrap();
+            let mut parser = GeneralParser::new(bracket_spec(), lex_spans(src));
+            assert!(parser.reparse(&old_tree, 0..src.len()).is_err(), "{:?}", src);
+        }
+    }
+
+    //Catch Send/Sync changes
+    #[test]
+    fn test_parser_send() {
+        fn assert_send<T: Send>() {}
+        assert_send::<GeneralParser<String, LexerVec<String>>>();
+    }
+
+    #[test]
+    fn test_parser_sync() {
+        fn assert_sync<T: Sync>() {}
+        assert_sync::<GeneralParser<String, LexerVec<String>>>();
+    }
+
+    #[test]
+    fn test_boxed_parser_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<GeneralParser<String, BoxedLexer<String>>>();
+    }
+
+    #[test]
+    fn test_boxed_lexers_in_one_vec() {
+        let text = StrLexer::new("a*b", |rest| {
+            let c = rest.chars().next().unwrap();
+            let token = match c {
+                '+' => TestToken::Add, 
+                '*' => TestToken::Mul, 
+                c => TestToken::Ident(c.to_string()), 
+            };
+            Some((token, c.len_utf8()))
+        }).unwrap();
+        let parsers = vec![
+            GeneralParser::new_boxed(arith_spec(), Box::new(lex("a + b"))), 
+            GeneralParser::new_boxed(arith_spec(), Box::new(text)), 
+        ];
+        let results: Vec<_> = parsers.into_iter().map(|mut parser| parser.parse()).collect();
+        assert_eq!(results, vec![
+            Ok(binary(TestToken::Add, ident("a"), ident("b"))), 
+            Ok(binary(TestToken::Mul, ident("a"), ident("b"))), 
+        ]);
+    }
+
+    #[test]
+    fn test_parse_from_entry_levels() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b * c"));
+        assert_eq!(parser.parse_from(PrecedenceLevel::Root), Ok(binary(TestToken::Add, 
+            ident("a"), 
+            binary(TestToken::Mul, ident("b"), ident("c")))));
+
+        //Starting at First stops before the Add, which doesn't bind tighter. 
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b * c"));
+        assert_eq!(parser.parse_expression(PrecedenceLevel::First), Ok(ident("a")));
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a * b * c"));
+        assert_eq!(parser.parse_from(PrecedenceLevel::Second), Ok(ident("a")));
+    }
+
+    #[test]
+    fn test_iterate_top_level_expressions() {
+        let parser = GeneralParser::new(arith_spec(), lex("a + b c * d e"));
+        let items: Vec<_> = parser.collect();
+        assert_eq!(items, vec![
+            Ok(binary(TestToken::Add, ident("a"), ident("b"))), 
+            Ok(binary(TestToken::Mul, ident("c"), ident("d"))), 
+            Ok(ident("e")), 
+        ]);
+    }
+
+    #[test]
+    fn test_iterate_past_stray_closer() {
+        let parser = GeneralParser::new(bracket_spec(), lex("a ) b"));
+        let items: Vec<_> = parser.collect();
+        assert_eq!(items, vec![
+            Ok(ident("a")), 
+            Err(ParseError::UnexpectedClosing{token: TestToken::Ident(")".into())}), 
+            Ok(ident("b")), 
+        ]);
+
+        let parser = GeneralParser::new(bracket_spec(), lex("a )"));
+        assert_eq!(parser.collect::<Vec<_>>(), vec![
+            Ok(ident("a")), 
+            Err(ParseError::UnexpectedClosing{token: TestToken::Ident(")".into())}), 
+        ]);
+    }
+
+    #[test]
+    fn test_iterate_past_rule_splicing_its_token_back() {
+        //Yielded as NoProgress and skipped, instead of Ok(m) forever
+        let parser = GeneralParser::new(splice_back_spec(), lex("a m b"));
+        assert_eq!(parser.collect::<Vec<_>>(), vec![
+            Ok(ident("a")), 
+            Err(ParseError::NoProgress{token: TestToken::Ident("m".into())}), 
+            Ok(ident("b")), 
+        ]);
+    }
+
+    #[test]
+    fn test_iterate_distinguishes_eof_from_error() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a b +"));
+        assert_eq!(parser.next(), Some(Ok(ident("a"))));
+        assert_eq!(parser.next(), Some(Err(ParseError::UnexpectedEof)));
+        assert_eq!(parser.next(), None);
+
+        let mut parser = GeneralParser::new(arith_spec(), lex(""));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn test_precedence_table_regroups() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b * c"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            ident("a"), 
+            binary(TestToken::Mul, ident("b"), ident("c")))));
+
+        //Same spec, but Add now binds tighter than Mul
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b * c"));
+        parser.set_precedence_table(|token: &TestToken| match *token {
+            TestToken::Add => Some(PrecedenceLevel::Third), 
+            _ => None
+        });
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Mul, 
+            binary(TestToken::Add, ident("a"), ident("b")), 
+            ident("c"))));
+    }
+
+    #[test]
+    fn test_parse_optional_present() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b;"));
+        assert_eq!(parser.parse_optional(PrecedenceLevel::Root, &[TestToken::Semi]), 
+            Ok(Some(binary(TestToken::Add, ident("a"), ident("b")))));
+        assert_eq!(parser.consume(TestToken::Semi), Ok(()));
+    }
+
+    #[test]
+    fn test_parse_optional_absent() {
+        let mut parser = GeneralParser::new(arith_spec(), lex(";"));
+        assert_eq!(parser.parse_optional(PrecedenceLevel::Root, &[TestToken::Semi]), Ok(None));
+        //The terminator is left for the caller
+        assert_eq!(parser.consume(TestToken::Semi), Ok(()));
+        assert_eq!(parser.parse_optional(PrecedenceLevel::Root, &[TestToken::Semi]), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_repeated_empty() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("; a"));
+        assert_eq!(parser.parse_repeated(PrecedenceLevel::Root, &TestToken::Semi), Ok(vec![]));
+        //stop token was consumed
+        assert_eq!(parser.parse(), Ok(ident("a")));
+    }
+
+    #[test]
+    fn test_parse_repeated_items() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a b * c d;"));
+        assert_eq!(parser.parse_repeated(PrecedenceLevel::Root, &TestToken::Semi), Ok(vec![
+            ident("a"), 
+            binary(TestToken::Mul, ident("b"), ident("c")), 
+            ident("d"), 
+        ]));
+        assert_eq!(parser.next(), None);
+    }
+
+    #[test]
+    fn test_parse_repeated_missing_stop() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a b"));
+        assert_eq!(parser.parse_repeated(PrecedenceLevel::Root, &TestToken::Semi), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_parse_stream_is_lazy() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b c * d e"));
+        {
+            let mut stream = parser.parse_stream();
+            assert_eq!(stream.next(), Some(Ok(binary(TestToken::Add, ident("a"), ident("b")))));
+        }
+        //Nothing past the first item was touched
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Mul, ident("c"), ident("d"))));
+        assert_eq!(parser.parse_stream().collect::<Vec<_>>(), vec![Ok(ident("e"))]);
+    }
+
+    #[test]
+    fn test_parse_stream_stops_at_error() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a ; b"));
+        assert_eq!(parser.parse_stream().collect::<Vec<_>>(), vec![
+            Ok(ident("a")), 
+            Err(ParseError::MissingRule{token: TestToken::Semi, ty: "Null".into()}), 
+        ]);
+    }
+
+    #[test]
+    fn test_parse_stream_stops_at_rule_splicing_its_token_back() {
+        let mut parser = GeneralParser::new(splice_back_spec(), lex("a m b"));
+        assert_eq!(parser.parse_stream().collect::<Vec<_>>(), vec![
+            Ok(ident("a")), 
+            Err(ParseError::NoProgress{token: TestToken::Ident("m".into())}), 
+        ]);
+    }
+
+    #[test]
+    fn test_error_recovery_inserts_error_node() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + ; * b"));
+        assert_eq!(parser.parse(), Err(ParseError::MissingRule{token: TestToken::Semi, ty: "Null".into()}));
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + ; * b"));
+        parser.set_error_recovery(true);
+        let error = Node::Error{
+            message: ParseError::MissingRule{token: TestToken::Semi, ty: "Null".into()}.to_string(), 
+            recovered_at: Some(TestToken::Semi)
+        };
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            ident("a"), 
+            binary(TestToken::Mul, error, ident("b")))));
+    }
+
+    #[test]
+    fn test_error_token_is_lexical_error() {
+        let mut spec = arith_spec();
+        spec.set_error_token(TestToken::Unknown(' '));
+        let spec = spec.freeze();
+        let mut parser = GeneralParser::new(spec.clone(), lex("a + ? * b"));
+        assert_eq!(parser.parse(), Err(ParseError::LexicalError{token: TestToken::Unknown('?')}));
+
+        //Recovery skips it, leaving an Error node
+        let mut parser = GeneralParser::new(spec.clone(), lex("a + ? * b"));
+        parser.set_error_recovery(true);
+        let error = Node::Error{
+            message: ParseError::LexicalError{token: TestToken::Unknown('?')}.to_string(), 
+            recovered_at: Some(TestToken::Unknown('?'))
+        };
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            ident("a"), 
+            binary(TestToken::Mul, error, ident("b")))));
+
+        //Never an operator, even with a default left rule
+        let mut spec = arith_spec();
+        spec.set_error_token(TestToken::Unknown(' '));
+        spec.set_default_left(PrecedenceLevel::Eighth, |_, _, _, node| Ok(node));
+        let mut parser = GeneralParser::new(spec, lex("a ? b"));
+        assert_eq!(parser.parse(), Ok(ident("a")));
+        assert_eq!(parser.parse(), Err(ParseError::LexicalError{token: TestToken::Unknown('?')}));
+    }
+
+    #[test]
+    fn test_default_null_rule() {
+        let mut spec = arith_spec();
+        spec.set_default_null(PrecedenceLevel::Root, |_, token, _| {
+            Ok(Node::Simple(TestToken::Ident(token.to_string())))
+        });
+        let mut parser = GeneralParser::new(spec, lex("a + ;"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, ident("a"), ident(";"))));
+    }
+
+    #[test]
+    fn test_default_left_rule() {
+        let mut spec = arith_spec();
+        //Juxtaposition: an unregistered token after an expression applies it
+        spec.set_default_left(PrecedenceLevel::Third, |_, token, _, node| {
+            Ok(Node::Composite{token, children: vec![node], span: None})
+        });
+        let mut parser = GeneralParser::new(spec, lex("a + b;"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            ident("a"), 
+            Node::Composite{token: TestToken::Semi, children: vec![ident("b")], span: None})));
+    }
+
+    #[test]
+    fn test_rule_branches_on_at_end() {
+        //A trailing ; ends the input, otherwise it separates two expressions
+        let mut spec = arith_spec();
+        spec.add_left_assoc(TestToken::Semi, PrecedenceLevel::First, |parser, token, lbp, node| {
+            if parser.at_end() {
+                Ok(Node::Composite{token, children: vec![node], span: None})
+            } else {
+                Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+            }
+        }).unwrap();
+
+        let mut parser = GeneralParser::new(spec.clone(), lex("a;"));
+        assert!(!parser.at_end());
+        assert_eq!(parser.parse(), Ok(Node::Composite{token: TestToken::Semi, children: vec![ident("a")], span: None}));
+        assert!(parser.at_end());
+
+        let mut parser = GeneralParser::new(spec, lex("a; b"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Semi, ident("a"), ident("b"))));
+    }
+
+    //a, b, c) with Semi standing in for the comma and Mul for the bracket
+    #[test]
+    fn test_parse_sequence_keeps_last_item() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; b; c * e"));
+        assert_eq!(parser.parse_sequence(PrecedenceLevel::Second, Some(TestToken::Semi), Some(TestToken::Mul)), vec![
+            Ok(ident("a")), Ok(ident("b")), Ok(ident("c")), 
+        ]);
+        assert_eq!(parser.parse(), Ok(ident("e")));
+    }
+
+    #[test]
+    fn test_parse_sequence_all_reports_every_bad_item() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; +; b; c d; e * f"));
+        assert_eq!(parser.parse_sequence_all(PrecedenceLevel::Second, Some(TestToken::Semi), Some(TestToken::Mul)), vec![
+            Ok(ident("a")), 
+            Err(ParseError::MissingRule{token: TestToken::Add, ty: "Null".into()}), 
+            Ok(ident("b")), 
+            Ok(ident("c")), 
+            Err(ParseError::ConsumeFailed{expected: TestToken::Semi, found: TestToken::Ident("d".into())}), 
+            Ok(ident("e")), 
+        ]);
+        assert_eq!(parser.parse(), Ok(ident("f")));
+
+        //parse_sequence stops at the first one
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; +; b; c d; e * f"));
+        assert_eq!(parser.parse_sequence(PrecedenceLevel::Second, Some(TestToken::Semi), Some(TestToken::Mul)).len(), 2);
+    }
+
+    #[test]
+    fn test_parse_sequence_bounded() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; b; c *"));
+        assert_eq!(parser.parse_sequence_bounded(PrecedenceLevel::Second, Some(TestToken::Semi), Some(TestToken::Mul), 3), vec![
+            Ok(ident("a")), Ok(ident("b")), Ok(ident("c")), 
+        ]);
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; b; c; d *"));
+        assert_eq!(parser.parse_sequence_bounded(PrecedenceLevel::Second, Some(TestToken::Semi), Some(TestToken::Mul), 3), vec![
+            Ok(ident("a")), Ok(ident("b")), Ok(ident("c")), 
+            Err(ParseError::SequenceTooLong{limit: 3}), 
+        ]);
+        //Stopped before the fourth item
+        assert_eq!(parser.parse_from(PrecedenceLevel::Second), Ok(ident("d")));
+    }
+
+    #[test]
+    fn test_spec_entry_level() {
+        let mut spec = arith_spec();
+        spec.set_entry(PrecedenceLevel::First);
+        let items: Vec<_> = GeneralParser::new(spec, lex("a + b * c")).collect();
+        //Add doesn't bind tighter than First, so each operand stands alone
+        assert_eq!(items, vec![
+            Ok(ident("a")), 
+            Err(ParseError::MissingRule{token: TestToken::Add, ty: "Null".into()}), 
+            Ok(binary(TestToken::Mul, ident("b"), ident("c"))), 
+        ]);
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, ident("a"), ident("b"))));
+    }
+
+    #[test]
+    fn test_consume_one_of() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("*;a"));
+        assert_eq!(parser.consume_one_of(&[TestToken::Semi, TestToken::Mul]), Ok(TestToken::Mul));
+        assert_eq!(parser.consume_one_of(&[TestToken::Semi, TestToken::Mul]), Ok(TestToken::Semi));
+        assert_eq!(parser.consume_one_of(&[TestToken::Semi, TestToken::Mul]), Err(ParseError::ConsumeOneFailed{
+            expected: vec![TestToken::Semi, TestToken::Mul], 
+            found: TestToken::Ident("a".into())
+        }));
+        //Nothing consumed on failure
+        assert_eq!(parser.parse(), Ok(ident("a")));
+        assert_eq!(parser.consume_one_of(&[TestToken::Semi]), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_clean_end_vs_unexpected_eof() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a"));
+        assert_eq!(parser.parse(), Ok(ident("a")));
+        //Nothing left to start an expression with
+        assert_eq!(parser.parse(), Err(ParseError::Incomplete));
+        assert_eq!(parser.consume(TestToken::Semi), Err(ParseError::UnexpectedEof));
+
+        //Out of tokens inside the Mul rule
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b *"));
+        assert_eq!(parser.parse(), Err(ParseError::UnexpectedEof));
+        assert_eq!(parser.parse(), Err(ParseError::Incomplete));
+    }
+
+    #[test]
+    fn test_fork_speculative_parse() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b * c ; d"));
+        let mut fork = parser.fork();
+        assert_eq!(fork.parse(), Ok(binary(TestToken::Add, ident("a"), binary(TestToken::Mul, ident("b"), ident("c")))));
+        assert_eq!(fork.peek_token(), Some(&TestToken::Semi));
+        //The original hasn't moved
+        assert_eq!(parser.lexer.position(), Some(0));
+        assert_eq!(parser.parse_expr(PrecedenceLevel::First), Ok(ident("a")));
+        //A fork picks up where its parser is
+        let mut fork = parser.fork();
+        assert_eq!(fork.consume(TestToken::Add), Ok(()));
+        assert_eq!(parser.peek_token(), Some(&TestToken::Add));
+    }
+
+    #[test]
+    fn test_consumed_kinds() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a*b*c"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Mul, binary(TestToken::Mul, ident("a"), ident("b")), ident("c"))));
+        assert!(parser.consumed_kinds().is_empty());
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a*b*c"));
+        parser.enable_kind_counts();
+        parser.parse().unwrap();
+        let kinds = parser.consumed_kinds();
+        assert_eq!(kinds.get(&discriminant(&TestToken::Mul)), Some(&2));
+        assert_eq!(kinds.get(&discriminant(&TestToken::Ident(String::new()))), Some(&3));
+        assert_eq!(kinds.get(&discriminant(&TestToken::Add)), None);
+    }
+
+    #[test]
+    fn test_parse_expr_with_floor_stops_at_comma() {
+        let comma = || TestToken::Ident(",".into());
+        let mut spec = bracket_spec();
+        spec.add_left_exact(comma(), PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec.add_opening(TestToken::Ident("(".into()));
+        let spec = spec.freeze();
+
+        //An operator to parse_expr
+        let mut parser = GeneralParser::new(spec.clone(), lex("a, b"));
+        assert_eq!(parser.parse_expr(PrecedenceLevel::Root), Ok(binary(comma(), ident("a"), ident("b"))));
+
+        let mut parser = GeneralParser::new(spec.clone(), lex("a, b"));
+        assert_eq!(parser.parse_expr_with_floor(PrecedenceLevel::Root, &[comma()]), Ok(ident("a")));
+        assert_eq!(parser.consume(comma()), Ok(()));
+        assert_eq!(parser.parse_expr_with_floor(PrecedenceLevel::Root, &[comma()]), Ok(ident("b")));
+
+        //Deeper in the subexpression too, but not inside brackets
+        let mut parser = GeneralParser::new(spec, lex("a + (b, c) * d, e"));
+        assert_eq!(parser.parse_expr_with_floor(PrecedenceLevel::Root, &[comma()]), Ok(binary(TestToken::Add, 
+            ident("a"), 
+            binary(TestToken::Mul, binary(comma(), ident("b"), ident("c")), ident("d")))));
+        assert_eq!(parser.peek_token(), Some(&comma()));
+    }
+
+    #[test]
+    fn test_empty_vs_truncated_input() {
+        let mut parser = GeneralParser::new(arith_spec(), lex(""));
+        assert_eq!(parser.parse(), Err(ParseError::EmptyInput));
+        assert_eq!(parser.parse_expr(PrecedenceLevel::Second), Err(ParseError::EmptyInput));
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a +"));
+        assert_eq!(parser.parse(), Err(ParseError::UnexpectedEof));
+        assert_eq!(parser.parse(), Err(ParseError::Incomplete));
+
+        //Spliced tokens count as input
+        let mut parser = GeneralParser::new(arith_spec(), lex(""));
+        parser.splice(vec![TestToken::Ident("a".into())]).unwrap();
+        assert_eq!(parser.parse(), Ok(ident("a")));
+        assert_eq!(parser.parse(), Err(ParseError::Incomplete));
+    }
+
+    #[test]
+    fn test_parse_sequence_eof() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; b"));
+        assert_eq!(parser.parse_sequence(PrecedenceLevel::Second, Some(TestToken::Semi), None), 
+            vec![Ok(ident("a")), Ok(ident("b"))]);
+
+        //The closing token never came
+        let mut parser = GeneralParser::new(arith_spec(), lex("a; b;"));
+        assert_eq!(parser.parse_sequence(PrecedenceLevel::Second, Some(TestToken::Semi), Some(TestToken::Mul)), 
+            vec![Ok(ident("a")), Ok(ident("b")), Err(ParseError::UnexpectedEof)]);
+    }
+
+    #[test]
+    fn test_exact_rules_split_a_variant() {
+        //n is a prefix operator, every other identifier is a plain leaf
+        let mut spec = arith_spec();
+        spec.add_null_exact(TestToken::Ident("n".into()), PrecedenceLevel::Third, |parser, token, bp| {
+            Ok(Node::Composite{token, children: vec![parser.parse_expr(bp)?], span: None})
+        }).unwrap();
+        assert!(spec.add_null_exact(TestToken::Ident("n".into()), PrecedenceLevel::Third, |_, token, _| {
+            Ok(Node::Simple(token))
+        }).is_err());
+
+        let mut parser = GeneralParser::new(spec, lex("n a + b"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            Node::Composite{token: TestToken::Ident("n".into()), children: vec![ident("a")], span: None}, 
+            ident("b"))));
+    }
+
+    //( ) and [ ] as identifiers with exact rules
+    fn bracket_spec() -> ParserSpec<TestToken> {
+        let mut spec = arith_spec();
+        spec.add_null_exact(TestToken::Ident("(".into()), PrecedenceLevel::Root, |parser, _, bp| {
+            let inner = parser.parse_expr(bp)?;
+            parser.consume(TestToken::Ident(")".into()))?;
+            Ok(inner)
+        }).unwrap();
+        spec.add_closing(TestToken::Ident(")".into()));
+        spec.add_closing(TestToken::Ident("]".into()));
+        spec
+    }
+
+    #[test]
+    fn test_closing_token_ends_expression() {
+        //Every identifier has a left rule, closers included
+        let mut spec = bracket_spec();
+        spec.set_default_left(PrecedenceLevel::Third, |_, _, _, _| panic!("closer treated as an operator"));
+        let mut parser = GeneralParser::new(spec, lex("(a + b) * c"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Mul, binary(TestToken::Add, ident("a"), ident("b")), ident("c"))));
+    }
+
+    #[test]
+    fn test_closing_token_where_operand_expected() {
+        let mut parser = GeneralParser::new(bracket_spec(), lex("()"));
+        assert_eq!(parser.parse(), Err(ParseError::UnexpectedClosing{token: TestToken::Ident(")".into())}));
+
+        let mut parser = GeneralParser::new(bracket_spec(), lex("(a]"));
+        assert_eq!(parser.parse(), Err(ParseError::ConsumeFailed{
+            expected: TestToken::Ident(")".into()), 
+            found: TestToken::Ident("]".into())}));
+
+        let mut parser = GeneralParser::new(bracket_spec(), lex("a + ]"));
+        assert_eq!(parser.parse(), Err(ParseError::UnexpectedClosing{token: TestToken::Ident("]".into())}));
+    }
+
+    #[test]
+    fn test_splice_expands_macro() {
+        //m expands to 1 + 1 textually, so m * c is 1 + 1 * c
+        let mut spec = arith_spec();
+        spec.add_null_exact(TestToken::Ident("m".into()), PrecedenceLevel::Root, |parser, _, bp| {
+            //Looking ahead first fills the lookahead slot that splice has to drop
+            assert!(parser.next_binds_tighter_than(bp));
+            parser.splice(vec![TestToken::Ident("1".into()), TestToken::Add, TestToken::Ident("1".into())])?;
+            parser.parse_expr(bp)
+        }).unwrap();
+
+        let mut parser = GeneralParser::new(spec, lex("m * c"));
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            ident("1"), 
+            binary(TestToken::Mul, ident("1"), ident("c")))));
+        assert!(parser.at_end());
+    }
+
+    //Add and Mul, with no rule for identifiers
+    fn operator_spec() -> ParserSpec<TestToken> {
+        let mut spec = ParserSpec::new();
+        spec.add_left_assoc(TestToken::Add, PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec.add_left_assoc(TestToken::Mul, PrecedenceLevel::Second, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec
+    }
+
+    #[test]
+    fn test_backtracking_competing_null_rules() {
+        //An identifier is a statement if a ; follows it, otherwise a leaf
+        let statement = |parser: &mut BacktrackingParser<TestToken, LexerVec<TestToken>>| {
+            parser.add_null_alternative(TestToken::Ident("".into()), PrecedenceLevel::Root, |parser, token, _| {
+                parser.consume(TestToken::Semi)?;
+                Ok(Node::Composite{token: TestToken::Semi, children: vec![Node::Simple(token)], span: None})
+            });
+            parser.add_null_alternative(TestToken::Ident("".into()), PrecedenceLevel::Root, |_, token, _| {
+                Ok(Node::Simple(token))
+            });
+        };
+        let semi = |node| Node::Composite{token: TestToken::Semi, children: vec![node], span: None};
+
+        let mut parser = BacktrackingParser::new(operator_spec(), lex("a + b;"));
+        statement(&mut parser);
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, ident("a"), semi(ident("b")))));
+        assert!(parser.at_end());
+
+        let mut parser = BacktrackingParser::new(operator_spec(), lex("a; * b"));
+        statement(&mut parser);
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Mul, semi(ident("a")), ident("b"))));
+
+        //Without the fallback the failure of the first rule is final
+        let mut parser = BacktrackingParser::new(operator_spec(), lex("a + b"));
+        parser.add_null_alternative(TestToken::Ident("".into()), PrecedenceLevel::Root, |parser, token, _| {
+            parser.consume(TestToken::Semi)?;
+            Ok(Node::Simple(token))
+        });
+        assert_eq!(parser.parse(), Err(ParseError::ConsumeFailed{expected: TestToken::Semi, found: TestToken::Add}));
+    }
+
+    #[test]
+    fn test_backtracking_left_alternative_after_spec_rule() {
+        //The spec's Mul wants a ; after its right operand, the alternative doesn't
+        let mut spec = ParserSpec::new();
+        spec.add_null_assoc(TestToken::Ident("".into()), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc(TestToken::Add, PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec.add_left_assoc(TestToken::Mul, PrecedenceLevel::Second, |parser, token, lbp, node| {
+            let rhs = parser.parse_expr(lbp)?;
+            parser.consume(TestToken::Semi)?;
+            Ok(Node::Composite{token: TestToken::Semi, children: vec![binary(token, node, rhs)], span: None})
+        }).unwrap();
+        let backtracking = |src| {
+            let mut parser = BacktrackingParser::new(spec.clone(), lex(src));
+            parser.add_left_alternative(TestToken::Mul, PrecedenceLevel::Second, |parser, token, lbp, node| {
+                Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+            });
+            parser
+        };
+
+        let mut parser = backtracking("a * b;");
+        assert_eq!(parser.parse(), Ok(Node::Composite{token: TestToken::Semi, children: vec![
+            binary(TestToken::Mul, ident("a"), ident("b"))], span: None}));
+
+        let mut parser = backtracking("a * b + c");
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, 
+            binary(TestToken::Mul, ident("a"), ident("b")), 
+            ident("c"))));
+        assert!(parser.at_end());
+    }
+
+    #[test]
+    fn test_memoized_backtracking_visits_each_position_once() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        //( e ) ; and ( e ) compete for every open paren. Without memoization 
+        // each level parses its contents twice, 2^depth leaves in total. 
+        let leaves = |memoize: bool, src: &str| {
+            let count = Arc::new(AtomicUsize::new(0));
+            let open = TestToken::Ident("(".into());
+            let close = TestToken::Ident(")".into());
+            let mut parser = BacktrackingParser::new(operator_spec(), lex(src));
+            if memoize {
+                parser.enable_memoization();
+            }
+            {
+                let (open, close) = (open.clone(), close.clone());
+                parser.add_null_alternative(open.clone(), PrecedenceLevel::Root, move |parser, token, _| {
+                    if token != open {
+                        return Err(ParseError::MissingRule{token, ty: "Null".into()})
+                    }
+                    let inner = parser.parse_expr(PrecedenceLevel::Root)?;
+                    parser.consume(close.clone())?;
+                    parser.consume(TestToken::Semi)?;
+                    Ok(inner)
+                });
+            }
+            {
+                let count = count.clone();
+                parser.add_null_alternative(open.clone(), PrecedenceLevel::Root, move |parser, token, _| {
+                    if token != open {
+                        count.fetch_add(1, Ordering::SeqCst);
+                        return Ok(Node::Simple(token))
+                    }
+                    let inner = parser.parse_expr(PrecedenceLevel::Root)?;
+                    parser.consume(close.clone())?;
+                    Ok(inner)
+                });
+            }
+            assert_eq!(parser.parse(), Ok(ident("a")));
+            assert!(parser.at_end());
+            count.load(Ordering::SeqCst)
+        };
+        let src = format!("{}a{}", "(".repeat(12), ")".repeat(12));
+        assert_eq!(leaves(false, &src), 1 << 12);
+        assert_eq!(leaves(true, &src), 1);
+    }
+
+    #[test]
+    fn test_memo_dropped_on_splice() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b"));
+        parser.enable_memoization();
+        assert_eq!(parser.parse_expr(PrecedenceLevel::First), Ok(ident("a")));
+        assert_eq!(parser.memo.as_ref().map(|memo| memo.len()), Some(1));
+        parser.splice(vec![TestToken::Mul, TestToken::Ident("c".into())]).unwrap();
+        assert_eq!(parser.memo.as_ref().map(|memo| memo.len()), Some(0));
+    }
+
+    //Single char tokens with spans, ( and ) are identifiers
+    fn lex_spans(src: &str) -> StrLexer<TestToken> {
+        StrLexer::new(src, |rest| {
+            let c = rest.chars().next().unwrap();
+            let token = match c {
+                '+' => TestToken::Add, 
+                '*' => TestToken::Mul, 
+                ';' => TestToken::Semi, 
+                c => TestToken::Ident(c.to_string()), 
+            };
+            Some((token, c.len_utf8()))
+        }).unwrap()
+    }
+
+    #[test]
+    fn test_spanned_parenthesized_expression() {
+        let mut spec = arith_spec();
+        spec.add_null_exact(TestToken::Ident("(".into()), PrecedenceLevel::Root, |parser, _, bp| {
+            let res = parser.parse_expr(bp)?;
+            parser.consume(TestToken::Ident(")".into()))?;
+            Ok(res)
+        }).unwrap();
+        let mut parser = GeneralParser::new(spec, lex_spans("x * (a + b)"));
+        parser.set_spanned(true);
+        let tree = parser.parse().unwrap();
+
+        let span = |start, end, column| Span{start, end, line: 1, column};
+        assert_eq!(tree.span(), Some(span(0, 11, 1)));
+        //Leaves have no span
+        assert_eq!(tree.children()[0].span(), None);
+        //The parenthesized operand covers the parentheses, not just a + b
+        let sum = &tree.children()[1];
+        assert_eq!(sum.span(), Some(span(4, 11, 5)));
+
+        assert_eq!(tree.strip_spans(), binary(TestToken::Mul, 
+            ident("x"), 
+            binary(TestToken::Add, ident("a"), ident("b"))));
+    }
+
+    #[test]
+    fn test_spanned_mode_needs_spans() {
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b"));
+        parser.set_spanned(true);
+        assert_eq!(parser.parse(), Ok(binary(TestToken::Add, ident("a"), ident("b"))));
+    }
+
+    #[test]
+    fn test_reparse_reuses_untouched_subtrees() {
+        let mut spec = bracket_spec();
+        spec.add_opening(TestToken::Ident("(".into()));
+        let spec = spec.freeze();
+        let parse = |src| {
+            let mut parser = GeneralParser::new(spec.clone(), lex_spans(src));
+            parser.set_spanned(true);
+            parser.parse().unwrap()
+        };
+        let old = parse("(a + b) * (c + d)");
+
+        //d becomes e: the first parenthesized operand is taken over whole
+        let mut parser = GeneralParser::new(spec.clone(), lex_spans("(a + b) * (c + e)"));
+        assert_eq!(parser.reparse(&old, 15..16), Ok(parse("(a + b) * (c + e)")));
+        assert_eq!(parser.reused(), 1);
+
+        //Inserting in front moves both, spans and all, but not the product, 
+        // which x + now comes before
+        let mut parser = GeneralParser::new(spec.clone(), lex_spans("x + (a + b) * (c + d)"));
+        assert_eq!(parser.reparse(&old, 0..4), Ok(parse("x + (a + b) * (c + d)")));
+        assert_eq!(parser.reused(), 2);
+
+        //An edit inside one leaves the other
+        let mut parser = GeneralParser::new(spec.clone(), lex_spans("(a * b) * (c + d)"));
+        assert_eq!(parser.reparse(&old, 3..4), Ok(parse("(a * b) * (c + d)")));
+        assert_eq!(parser.reused(), 1);
+
+        //Without brackets, a * b is only reused where * still binds
+        let old = parse("a * b + c");
+        let mut parser = GeneralParser::new(spec.clone(), lex_spans("a * b + d"));
+        assert_eq!(parser.reparse(&old, 8..9), Ok(parse("a * b + d")));
+        assert_eq!(parser.reused(), 1);
+        let mut parser = GeneralParser::new(spec.clone(), lex_spans("x * a * b + c"));
+        assert_eq!(parser.reparse(&old, 0..4), Ok(parse("x * a * b + c")));
+        assert_eq!(parser.reused(), 0);
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let args = |src| {
+            let mut parser = GeneralParser::new(arith_spec(), lex(src));
+            let res = parser.parse_args(TestToken::Ident("(".into()), TestToken::Semi, TestToken::Ident(")".into()), PrecedenceLevel::Root);
+            (res, parser.at_end())
+        };
+        assert_eq!(args("()"), (Ok(vec![]), true));
+        assert_eq!(args("(a)"), (Ok(vec![ident("a")]), true));
+        assert_eq!(args("(a; b + c; d)"), (Ok(vec![
+            ident("a"), 
+            binary(TestToken::Add, ident("b"), ident("c")), 
+            ident("d")]), true));
+
+        assert_eq!(args("a)").0, Err(ParseError::ConsumeFailed{expected: TestToken::Ident("(".into()), found: TestToken::Ident("a".into())}));
+        assert_eq!(args("(a b)").0, Err(ParseError::ConsumeOneFailed{
+            expected: vec![TestToken::Semi, TestToken::Ident(")".into())], 
+            found: TestToken::Ident("b".into())}));
+    }
+
+    #[test]
+    fn test_skip_balanced_nested() {
+        let paren = |s: &str| TestToken::Ident(s.into());
+        let mut parser = GeneralParser::new(arith_spec(), lex("(a (b) (c (d))) + e"));
+        let skipped = parser.skip_balanced(paren("("), paren(")")).unwrap();
+        assert_eq!(skipped, "a(b)(c(d))".chars().map(|c| paren(&c.to_string())).collect::<Vec<_>>());
+        assert_eq!(parser.peek_token(), Some(&TestToken::Add));
+    }
+
+    #[test]
+    fn test_skip_balanced_unbalanced() {
+        let paren = |s: &str| TestToken::Ident(s.into());
+        let mut parser = GeneralParser::new(arith_spec(), lex("(a (b)"));
+        assert_eq!(parser.skip_balanced(paren("("), paren(")")), Err(ParseError::Incomplete));
+        let mut parser = GeneralParser::new(arith_spec(), lex("a)"));
+        assert_eq!(parser.skip_balanced(paren("("), paren(")")), 
+            Err(ParseError::ConsumeFailed{expected: paren("("), found: paren("a")}));
+    }
+
+    #[test]
+    fn test_consume_if_matchers() {
+        use token::{Exact, SameKind};
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a b ; +"));
+        assert_eq!(parser.consume_if(&Exact(TestToken::Ident("b".into()))), 
+            Err(ParseError::ConsumeIfFailed{found: TestToken::Ident("a".into())}));
+        assert_eq!(parser.consume_if(&Exact(TestToken::Ident("a".into()))), Ok(TestToken::Ident("a".into())));
+        assert_eq!(parser.consume_if(&SameKind(TestToken::Ident(String::new()))), Ok(TestToken::Ident("b".into())));
+        assert_eq!(parser.consume_if(&SameKind(TestToken::Ident(String::new()))), 
+            Err(ParseError::ConsumeIfFailed{found: TestToken::Semi}));
+        assert_eq!(parser.consume_if(&|tk: &TestToken| *tk != TestToken::Add), Ok(TestToken::Semi));
+        assert_eq!(parser.consume_if(&Exact(TestToken::Add)), Ok(TestToken::Add));
+        assert_eq!(parser.consume_if(&Exact(TestToken::Add)), Err(ParseError::UnexpectedEof));
+    }
+
+    #[test]
+    fn test_parse_n() {
+        let parse_n = |src, n| GeneralParser::new(arith_spec(), lex(src)).parse_n(PrecedenceLevel::Root, n);
+        assert_eq!(parse_n("", 0), Ok(vec![]));
+        assert_eq!(parse_n("a b", 0), Ok(vec![]));
+        assert_eq!(parse_n("a + b c", 1), Ok(vec![binary(TestToken::Add, ident("a"), ident("b"))]));
+        assert_eq!(parse_n("a b * c d", 3), Ok(vec![
+            ident("a"), 
+            binary(TestToken::Mul, ident("b"), ident("c")), 
+            ident("d")]));
+
+        //Stops at the first failure
+        assert_eq!(parse_n("a b", 3), Err(ParseError::UnexpectedEof));
+        assert_eq!(parse_n("a ; b", 3), Err(ParseError::MissingRule{token: TestToken::Semi, ty: "Null".into()}));
+    }
+
+    #[test]
+    fn test_trace_events() {
+        use std::sync::{Arc, Mutex};
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b;"));
+        {
+            let events = events.clone();
+            parser.set_trace(move |event| events.lock().unwrap().push(event));
+        }
+        let sum = binary(TestToken::Add, ident("a"), ident("b"));
+        assert_eq!(parser.parse(), Ok(sum.clone()));
+        assert_eq!(parser.consume(TestToken::Semi), Ok(()));
+
+        assert_eq!(*events.lock().unwrap(), vec![
+            TraceEvent::EnterExpr{rbp: PrecedenceLevel::Root}, 
+            TraceEvent::NullRule{token: TestToken::Ident("a".into())}, 
+            TraceEvent::LeftRule{token: TestToken::Add}, 
+            TraceEvent::EnterExpr{rbp: PrecedenceLevel::First}, 
+            TraceEvent::NullRule{token: TestToken::Ident("b".into())}, 
+            TraceEvent::Exit{node_summary: ident("b").to_string()}, 
+            TraceEvent::Exit{node_summary: sum.to_string()}, 
+            TraceEvent::Consume{token: TestToken::Semi}, 
+        ]);
+    }
+
+    #[cfg(feature = "logging")]
+    #[test]
+    fn test_log_output() {
+        use std::sync::Mutex;
+
+        use log::{Log, Metadata, Record};
+
+        struct TestLogger(Mutex<Vec<String>>);
+
+        impl Log for TestLogger {
+            fn enabled(&self, metadata: &Metadata) -> bool {
+                metadata.target() == "prattle"
+            }
+
+            fn log(&self, record: &Record) {
+                if self.enabled(record.metadata()) {
+                    self.0.lock().unwrap().push(record.args().to_string());
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        let logger: &'static TestLogger = Box::leak(Box::new(TestLogger(Mutex::new(Vec::new()))));
+        log::set_logger(logger).unwrap();
+        log::set_max_level(log::LevelFilter::Trace);
+
+        let mut parser = GeneralParser::new(arith_spec(), lex("a + b;"));
+        assert!(parser.parse().is_ok());
+        assert!(parser.consume(TestToken::Semi).is_ok());
+
+        let lines = logger.0.lock().unwrap();
+        assert!(lines.contains(&"null rule for a at Root(0)".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"+ binds at First(5) against Root(0)".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"left rule for + at First(5)".to_string()), "{:?}", lines);
+        assert!(lines.contains(&"consumed ;".to_string()), "{:?}", lines);
+    }
+
+    //A broken lexer: next_token hands out the same token without moving on
+    struct StuckLexer(TestToken);
+
+    impl Lexer<TestToken> for StuckLexer {
+        fn peek(&self) -> Option<TestToken> {
+            Some(self.0.clone())
+        }
+
+        fn next_token(&mut self) -> TestToken {
+            self.0.clone()
+        }
+
+        fn position(&self) -> Option<usize> {
+            Some(0)
+        }
+    }
+
+    #[test]
+    fn test_no_progress_is_an_error() {
+        let stuck = TestToken::Ident("a".into());
+        let mut parser = GeneralParser::new(arith_spec(), StuckLexer(stuck.clone()));
+        assert_eq!(parser.parse_repeated(PrecedenceLevel::Root, &TestToken::Semi), 
+            Err(ParseError::NoProgress{token: stuck.clone()}));
+
+        let mut parser = GeneralParser::new(arith_spec(), StuckLexer(stuck.clone()));
+        assert_eq!(parser.parse_sequence(PrecedenceLevel::Root, None, None), 
+            vec![Err(ParseError::NoProgress{token: stuck})]);
+    }
+
+    //arith_spec plus a null rule for m that puts back the token it was given, 
+    //so the lexer moves but nothing is taken from the input
+    fn splice_back_spec() -> ParserSpec<TestToken> {
+        let mut spec = arith_spec();
+        spec.add_null_exact(TestToken::Ident("m".into()), PrecedenceLevel::Root, |parser, token, _| {
+            parser.splice(vec![token])?;
+            Ok(ident("m"))
+        }).unwrap();
+        spec
+    }
+
+    #[test]
+    fn test_rule_splicing_its_token_back_is_no_progress() {
+        let spec = splice_back_spec();
+        let stuck = TestToken::Ident("m".into());
+
+        let mut parser = GeneralParser::new(spec.clone(), lex("a m ;"));
+        assert_eq!(parser.parse_repeated(PrecedenceLevel::Root, &TestToken::Semi), 
+            Err(ParseError::NoProgress{token: stuck.clone()}));
+
+        let mut parser = GeneralParser::new(spec, lex("a m"));
+        assert_eq!(parser.parse_sequence(PrecedenceLevel::Root, None, None), 
+            vec![Ok(ident("a")), Err(ParseError::NoProgress{token: stuck})]);
+    }
+
+    //An implementation written against the original trait: only the five 
+    //required methods plus peek, every other method is a default
+    struct IdentsOnly(Vec<TestToken>);
+
+    impl Parser<TestToken> for IdentsOnly {
+        fn parse(&mut self) -> Result<Node<TestToken>, ParseError<TestToken>> {
+            self.parse_expr(PrecedenceLevel::Root)
+        }
+
+        fn parse_expr(&mut self, _rbp: PrecedenceLevel) -> Result<Node<TestToken>, ParseError<TestToken>> {
+            match self.0.first().cloned() {
+                Some(TestToken::Ident(name)) => {
+                    self.0.remove(0);
+                    Ok(ident(&name))
+                }, 
+                Some(token) => Err(ParseError::MissingRule{token, ty: "Null".into()}), 
+                None => Err(ParseError::Incomplete)
+            }
+        }
+
+        fn parse_sequence(&mut self, rbp: PrecedenceLevel, _sep: Option<TestToken>, _end_token: Option<TestToken>) -> Vec<Result<Node<TestToken>, ParseError<TestToken>>> {
+            let mut nodes = Vec::new();
+            while !self.0.is_empty() {
+                nodes.push(self.parse_expr(rbp));
+            }
+            nodes
+        }
+
+        fn next_binds_tighter_than(&mut self, _rbp: PrecedenceLevel) -> bool {
+            false
+        }
+
+        fn consume(&mut self, end_token: TestToken) -> Result<(), ParseError<TestToken>> {
+            match self.0.first().cloned() {
+                Some(ref found) if *found == end_token => {
+                    self.0.remove(0);
+                    Ok(())
+                }, 
+                Some(found) => Err(ParseError::ConsumeFailed{expected: end_token, found}), 
+                None => Err(ParseError::UnexpectedEof)
+            }
+        }
+
+        fn peek(&self) -> Option<TestToken> {
+            self.0.first().cloned()
+        }
+    }
+
+    #[test]
+    fn test_default_methods_on_minimal_parser() {
+        let a = TestToken::Ident("a".into());
+        let b = TestToken::Ident("b".into());
+        let (open, close) = (TestToken::Unknown('('), TestToken::Unknown(')'));
+        let mut parser = IdentsOnly(vec![open.clone(), a.clone(), TestToken::Add, b.clone(), close.clone(), 
+            a.clone(), b.clone(), TestToken::Semi, a.clone()]);
+        assert_eq!(parser.parse_args(open, TestToken::Add, close, PrecedenceLevel::Root), 
+            Ok(vec![ident("a"), ident("b")]));
+        assert_eq!(parser.parse_repeated(PrecedenceLevel::Root, &TestToken::Semi), Ok(vec![ident("a"), ident("b")]));
+        assert_eq!(parser.parse_optional(PrecedenceLevel::Root, &[TestToken::Semi]), Ok(Some(ident("a"))));
+        assert!(parser.at_end());
+        assert_eq!(parser.parse_n(PrecedenceLevel::Root, 1), Err(ParseError::UnexpectedEof));
+        assert_eq!(parser.consume_one_of(&[TestToken::Semi]), Err(ParseError::UnexpectedEof));
+        assert_eq!(parser.splice(vec![a.clone()]), Err(ParseError::SpliceUnsupported));
+
+        let mut parser = IdentsOnly(vec![a.clone(), TestToken::Mul]);
+        assert_eq!(parser.parse_expr_right(PrecedenceLevel::First), Ok(ident("a")));
+        assert_eq!(parser.consume_one_of(&[TestToken::Add, TestToken::Mul]), Ok(TestToken::Mul));
     }
 }
\ No newline at end of file
@@ -34,30 +34,388 @@
 //! When mul is defined with a higher precedence than add, that results in the 
 //! following grouping:
 //! > a + (b * c)
+//!
 //! where b*c is done before the addition. Many languages have either a defined
 //! or implicit precedence ordering of operators. 
 //! 
 //! For example, see [C++ Operator Precedence table](https://en.cppreference.com/w/cpp/language/operator_precedence)
 //! 
 
+use std::cmp::Ordering;
+use std::convert::TryFrom;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 
-#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+use token::Token;
+
+/// The named levels are 0, 5, 10, ... 40 (see u32::from), leaving room 
+/// between them for Custom levels. Levels are ordered by their value. 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum PrecedenceLevel {
-    Root    = 0, 
-    First   = 5, 
-    Second  = 10, 
-    Third   = 15, 
-    Fourth  = 20, 
-    Fifth   = 25, 
-    Sixth   = 30, 
-    Seventh = 35, 
-    Eighth  = 40,
+    Root, 
+    First, 
+    Second, 
+    Third, 
+    Fourth, 
+    Fifth, 
+    Sixth, 
+    Seventh, 
+    Eighth, 
+    ///A level at a value none of the named ones have, made with 
+    /// PrecedenceLevel::custom (or between). 
+    Custom(CustomLevel),
+}
+
+/// The value of a Custom level. Only PrecedenceLevel::custom makes one, so 
+/// a value with a named level is never a Custom level too. 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CustomLevel(u32);
+
+impl Ord for PrecedenceLevel {
+    fn cmp(&self, other: &PrecedenceLevel) -> Ordering {
+        u32::from(*self).cmp(&u32::from(*other))
+    }
 }
 
+impl PartialOrd for PrecedenceLevel {
+    fn partial_cmp(&self, other: &PrecedenceLevel) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Stepping between levels. 
+/// 
+/// prev/next move to the *adjacent named level* (Second.prev() is First), 
+/// not to the numeric value minus/plus one; a Custom level steps to the 
+/// named level on either side of it. Both saturate at the ends, so 
+/// Root.prev() is Root and Eighth.next() is Eighth (and any Custom level 
+/// above it as well). 
+/// 
+/// A right associative operator at level L recurses with L.prev(), so that 
+/// another operator at L still binds tighter than the recursion's rbp. 
+impl PrecedenceLevel {
+    pub fn prev(self) -> PrecedenceLevel {
+        use self::PrecedenceLevel::*;
+        match self {
+            Custom(CustomLevel(value)) => PrecedenceLevel::named_below(value), 
+            Root | First => Root, 
+            Second  => First, 
+            Third   => Second, 
+            Fourth  => Third, 
+            Fifth   => Fourth, 
+            Sixth   => Fifth, 
+            Seventh => Sixth, 
+            Eighth  => Seventh,
+        }
+    }
+
+    pub fn next(self) -> PrecedenceLevel {
+        use self::PrecedenceLevel::*;
+        match self {
+            Custom(CustomLevel(value)) => PrecedenceLevel::named_below(value).next(), 
+            Root    => First, 
+            First   => Second, 
+            Second  => Third, 
+            Third   => Fourth, 
+            Fourth  => Fifth, 
+            Fifth   => Sixth, 
+            Sixth   => Seventh, 
+            Seventh | Eighth => Eighth,
+        }
+    }
+
+    ///The level at `value`: the named level if there is one at that value, 
+    /// otherwise a Custom level. 
+    pub fn custom(value: u32) -> PrecedenceLevel {
+        PrecedenceLevel::try_from(value).unwrap_or(PrecedenceLevel::Custom(CustomLevel(value)))
+    }
+
+    ///The level at the numeric midpoint of `a` and `b` (in either order, 
+    /// rounding down), for slotting a new operator in between two existing 
+    /// ones without renumbering: between(First, Second) is the Custom level 
+    /// at 7. None if the two are less than two apart, leaving no value 
+    /// strictly between them. 
+    pub fn between(a: PrecedenceLevel, b: PrecedenceLevel) -> Option<PrecedenceLevel> {
+        let (lo, hi) = if a <= b { (u32::from(a), u32::from(b)) } else { (u32::from(b), u32::from(a)) };
+        let mid = lo + (hi - lo) / 2;
+        if lo < mid { Some(PrecedenceLevel::custom(mid)) } else { None }
+    }
+
+    //The highest named level below `value`, Root if there is none. 
+    fn named_below(value: u32) -> PrecedenceLevel {
+        let mut level = PrecedenceLevel::Root;
+        while level != PrecedenceLevel::Eighth && u32::from(level.next()) < value {
+            level = level.next();
+        }
+        level
+    }
+
+    ///The variant's name, "Root" through "Eighth", or "Custom". The numeric 
+    /// value is u32::from(level). 
+    pub fn name(&self) -> &'static str {
+        use self::PrecedenceLevel::*;
+        match *self {
+            Root    => "Root", 
+            First   => "First", 
+            Second  => "Second", 
+            Third   => "Third", 
+            Fourth  => "Fourth", 
+            Fifth   => "Fifth", 
+            Sixth   => "Sixth", 
+            Seventh => "Seventh", 
+            Eighth  => "Eighth", 
+            Custom(_) => "Custom",
+        }
+    }
+}
+
+/// Names for the levels by what usually sits at them, following C's 
+/// ordering from loosest to tightest, so a grammar can say 
+/// `PrecedenceLevel::ADDITIVE` rather than `PrecedenceLevel::Fifth`. They 
+/// are only conventions: a grammar's own aliases work just as well, 
+/// 
+/// ```rust
+/// # use prattle::precedence::PrecedenceLevel;
+/// const PIPELINE: PrecedenceLevel = PrecedenceLevel::First;
+/// ```
+/// 
+/// and from_name looks levels up by either kind of name, for grammars 
+/// loaded from configuration. 
+impl PrecedenceLevel {
+    pub const ASSIGNMENT: PrecedenceLevel = PrecedenceLevel::First;
+    pub const LOGICAL_OR: PrecedenceLevel = PrecedenceLevel::Second;
+    pub const LOGICAL_AND: PrecedenceLevel = PrecedenceLevel::Third;
+    pub const COMPARISON: PrecedenceLevel = PrecedenceLevel::Fourth;
+    pub const ADDITIVE: PrecedenceLevel = PrecedenceLevel::Fifth;
+    pub const MULTIPLICATIVE: PrecedenceLevel = PrecedenceLevel::Sixth;
+    pub const PREFIX: PrecedenceLevel = PrecedenceLevel::Seventh;
+    pub const POSTFIX: PrecedenceLevel = PrecedenceLevel::Eighth;
+
+    ///The level called `name`: a variant name as returned by name() 
+    /// ("Fifth"), or one of the semantic names above ("additive"), either 
+    /// in any case. 
+    pub fn from_name(name: &str) -> Option<PrecedenceLevel> {
+        use self::PrecedenceLevel::*;
+        let semantic = [
+            ("assignment", PrecedenceLevel::ASSIGNMENT), 
+            ("logical_or", PrecedenceLevel::LOGICAL_OR), 
+            ("logical_and", PrecedenceLevel::LOGICAL_AND), 
+            ("comparison", PrecedenceLevel::COMPARISON), 
+            ("additive", PrecedenceLevel::ADDITIVE), 
+            ("multiplicative", PrecedenceLevel::MULTIPLICATIVE), 
+            ("prefix", PrecedenceLevel::PREFIX), 
+            ("postfix", PrecedenceLevel::POSTFIX), 
+        ];
+        [Root, First, Second, Third, Fourth, Fifth, Sixth, Seventh, Eighth].iter()
+            .map(|&level| (level.name(), level))
+            .chain(semantic.iter().cloned())
+            .find(|&(candidate, _)| candidate.eq_ignore_ascii_case(name))
+            .map(|(_, level)| level)
+    }
+}
+
+///Name and value, as in ```Second(10)```. 
 impl Display for PrecedenceLevel {
     fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
-        write!(f, "(Precedence: {})", *self as u32)
+        write!(f, "{}({})", self.name(), u32::from(*self))
+    }
+}
+
+/// Returned when converting a number that isn't one of the defined level 
+/// values (0, 5, 10, ... 40) into a PrecedenceLevel. 
+#[derive(Clone, Copy, Debug, Eq, Fail, Hash, PartialEq)]
+#[fail(display = "{} is not a defined precedence level", value)]
+pub struct InvalidPrecedenceLevel {
+    pub value: u32
+}
+
+impl From<PrecedenceLevel> for u32 {
+    fn from(level: PrecedenceLevel) -> u32 {
+        use self::PrecedenceLevel::*;
+        match level {
+            Root    => 0, 
+            First   => 5, 
+            Second  => 10, 
+            Third   => 15, 
+            Fourth  => 20, 
+            Fifth   => 25, 
+            Sixth   => 30, 
+            Seventh => 35, 
+            Eighth  => 40, 
+            Custom(CustomLevel(value)) => value,
+        }
+    }
+}
+
+/// For precedences read from configuration or computed at runtime. Only the 
+/// exact values of the named levels convert; PrecedenceLevel::custom takes 
+/// any value. 
+impl TryFrom<u32> for PrecedenceLevel {
+    type Error = InvalidPrecedenceLevel;
+
+    fn try_from(value: u32) -> Result<PrecedenceLevel, InvalidPrecedenceLevel> {
+        use self::PrecedenceLevel::*;
+        match value {
+            0  => Ok(Root), 
+            5  => Ok(First), 
+            10 => Ok(Second), 
+            15 => Ok(Third), 
+            20 => Ok(Fourth), 
+            25 => Ok(Fifth), 
+            30 => Ok(Sixth), 
+            35 => Ok(Seventh), 
+            40 => Ok(Eighth), 
+            _  => Err(InvalidPrecedenceLevel{value})
+        }
+    }
+}
+
+/// Associativity of an infix operator, used to derive the (lbp, rbp) pair 
+/// stored for it in the ParserSpec. 
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Associativity {
+    /// a - b - c => (a - b) - c
+    Left, 
+    /// a ^ b ^ c => a ^ (b ^ c)
+    Right,
+}
+
+impl Associativity {
+    ///Returns the (lbp, rbp) to register for an operator at `level`. 
+    /// The rule closure receives lbp and recurses with it: left associative 
+    /// operators recurse at their own level, right associative ones at the 
+    /// level below, so that the next operator at `level` still binds. 
+    pub fn binding_powers(self, level: PrecedenceLevel) -> (PrecedenceLevel, PrecedenceLevel) {
+        match self {
+            Associativity::Left => (level, level), 
+            Associativity::Right => (level.prev(), level),
+        }
+    }
+}
+
+/// Runtime source of left binding powers, for languages where operator 
+/// precedence is user definable (custom operators in ML-family languages). 
+/// 
+/// When set on a GeneralParser, the table is asked for the level of every 
+/// token that has a left rule; returning None falls back to the level 
+/// registered in the ParserSpec. Associativity still comes from the spec: 
+/// a token registered as right associative recurses at level.prev(). 
+/// 
+/// Numeric precedences (from a config file, say) can be converted with 
+/// PrecedenceLevel::try_from. 
+pub trait PrecedenceTable<T: Token>: Send + Sync {
+    fn lbp(&self, token: &T) -> Option<PrecedenceLevel>;
+}
+
+impl<T: Token, F> PrecedenceTable<T> for F 
+    where F: Fn(&T) -> Option<PrecedenceLevel> + Send + Sync
+{
+    fn lbp(&self, token: &T) -> Option<PrecedenceLevel> {
+        self(token)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_prev_next_adjacent_variants() {
+        assert_eq!(PrecedenceLevel::Second.prev(), PrecedenceLevel::First);
+        assert_eq!(PrecedenceLevel::Second.next(), PrecedenceLevel::Third);
+        assert_eq!(PrecedenceLevel::First.prev(), PrecedenceLevel::Root);
+        assert_eq!(PrecedenceLevel::Seventh.next(), PrecedenceLevel::Eighth);
+    }
+
+    #[test]
+    fn test_display_name_and_value() {
+        assert_eq!(PrecedenceLevel::Second.to_string(), "Second(10)");
+        assert_eq!(PrecedenceLevel::Root.to_string(), "Root(0)");
+        assert_eq!(PrecedenceLevel::Eighth.name(), "Eighth");
+    }
+
+    #[test]
+    fn test_semantic_names() {
+        assert_eq!(u32::from(PrecedenceLevel::ASSIGNMENT), 5);
+        assert_eq!(u32::from(PrecedenceLevel::COMPARISON), 20);
+        assert_eq!(u32::from(PrecedenceLevel::ADDITIVE), 25);
+        assert_eq!(u32::from(PrecedenceLevel::MULTIPLICATIVE), 30);
+        assert_eq!(u32::from(PrecedenceLevel::POSTFIX), 40);
+        assert!(PrecedenceLevel::LOGICAL_OR < PrecedenceLevel::LOGICAL_AND);
+        assert!(PrecedenceLevel::MULTIPLICATIVE < PrecedenceLevel::PREFIX);
+
+        assert_eq!(PrecedenceLevel::from_name("additive"), Some(PrecedenceLevel::ADDITIVE));
+        assert_eq!(PrecedenceLevel::from_name("LOGICAL_AND"), Some(PrecedenceLevel::Third));
+        assert_eq!(PrecedenceLevel::from_name("Seventh"), Some(PrecedenceLevel::PREFIX));
+        assert_eq!(PrecedenceLevel::from_name("root"), Some(PrecedenceLevel::Root));
+        assert_eq!(PrecedenceLevel::from_name("exponent"), None);
+    }
+
+    #[test]
+    fn test_u32_conversions() {
+        assert_eq!(u32::from(PrecedenceLevel::Root), 0);
+        assert_eq!(u32::from(PrecedenceLevel::Third), 15);
+        assert_eq!(PrecedenceLevel::try_from(0), Ok(PrecedenceLevel::Root));
+        assert_eq!(PrecedenceLevel::try_from(40), Ok(PrecedenceLevel::Eighth));
+        assert_eq!(PrecedenceLevel::try_from(7), Err(InvalidPrecedenceLevel{value: 7}));
+        assert_eq!(PrecedenceLevel::try_from(45), Err(InvalidPrecedenceLevel{value: 45}));
+    }
+
+    #[test]
+    fn test_u32_round_trip() {
+        let mut level = PrecedenceLevel::Root;
+        loop {
+            assert_eq!(PrecedenceLevel::try_from(u32::from(level)), Ok(level));
+            if level == PrecedenceLevel::Eighth {
+                break
+            }
+            level = level.next();
+        }
+    }
+
+    #[test]
+    fn test_associativity_binding_powers() {
+        assert_eq!(Associativity::Left.binding_powers(PrecedenceLevel::Third), 
+            (PrecedenceLevel::Third, PrecedenceLevel::Third));
+        assert_eq!(Associativity::Right.binding_powers(PrecedenceLevel::Third), 
+            (PrecedenceLevel::Second, PrecedenceLevel::Third));
+    }
+
+    #[test]
+    fn test_between() {
+        use self::PrecedenceLevel::*;
+        assert_eq!(PrecedenceLevel::between(First, Third), Some(Second));
+        assert_eq!(PrecedenceLevel::between(Third, First), Some(Second));
+        assert_eq!(PrecedenceLevel::between(Root, Eighth), Some(Fourth));
+        assert_eq!(PrecedenceLevel::between(First, Fourth), Some(PrecedenceLevel::custom(12)));
+        assert_eq!(PrecedenceLevel::between(Third, Third), None);
+
+        //Adjacent levels have room in between
+        let level = PrecedenceLevel::between(First, Second).unwrap();
+        assert_eq!(u32::from(level), 7);
+        assert!(First < level && level < Second);
+        assert_eq!(level.to_string(), "Custom(7)");
+        assert_eq!((level.prev(), level.next()), (First, Second));
+        //...and so on until there are no values left
+        let level = PrecedenceLevel::between(First, level).unwrap();
+        assert_eq!(u32::from(level), 6);
+        assert_eq!(PrecedenceLevel::between(First, level), None);
+    }
+
+    #[test]
+    fn test_custom_levels() {
+        use self::PrecedenceLevel::*;
+        assert_eq!(PrecedenceLevel::custom(10), Second);
+        assert_eq!(PrecedenceLevel::try_from(12), Err(InvalidPrecedenceLevel{value: 12}));
+        assert!(PrecedenceLevel::custom(41) > Eighth);
+        assert_eq!(PrecedenceLevel::custom(41).prev(), Eighth);
+        assert_eq!(PrecedenceLevel::custom(41).next(), Eighth);
+        assert_eq!(PrecedenceLevel::custom(3).prev(), Root);
+    }
+
+    #[test]
+    fn test_prev_next_saturate() {
+        assert_eq!(PrecedenceLevel::Root.prev(), PrecedenceLevel::Root);
+        assert_eq!(PrecedenceLevel::Eighth.next(), PrecedenceLevel::Eighth);
     }
 }
\ No newline at end of file
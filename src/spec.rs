@@ -26,28 +26,72 @@
 //! call to the parser.)
 //! 
 //! ## Closure types:
-//! NullDenotation<T> = fn(&mut dyn Parser<T>, T, u32) -> Result<Node<T>, ParseError<T>>;
-//! LeftDenotation<T> = fn(&mut dyn Parser<T>, T, u32, Node<T>) -> Result<Node<T>, ParseError<T>>;
+//! NullDenotation<T> = Arc<dyn Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync>;
+//! LeftDenotation<T> = Arc<dyn Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync>;
 //! 
-//! where T is your token type. 
+//! where T is your token type. The add_* methods take any closure with a matching 
+//! signature (capturing state is fine) and wrap it in the Arc themselves, so 
+//! cloning a spec is cheap and the clones can build parsers on other threads. 
 //! 
 //! Tokens must implement the required traits: 
 //!     Clone + Debug + Display + PartialEq
 //! 
 //! Send + Sync + 'static are inherent and auto-implemented by the compiler on valid Token types.
 //! 
+//! ## One token, two roles
+//! A token can have a null rule and a left rule at once: the parser picks 
+//! the null rule where an expression starts and the left rule after an 
+//! operand. That's how `-` is both negation and subtraction; 
+//! add_prefix_and_infix registers the pair in one go: 
+//! 
+//! ```rust
+//! # extern crate prattle;
+//! # use std::fmt;
+//! use prattle::prelude::*;
+//! 
+//! #[derive(Clone, Debug, PartialEq)]
+//! enum Tk { Var(char), Minus }
+//! # impl fmt::Display for Tk {
+//! #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+//! # }
+//! 
+//! # fn main() {
+//! let mut spec = ParserSpec::new();
+//! spec.add_null_assoc(Tk::Var(' '), PrecedenceLevel::Root, |_, token, _| Ok(Node::leaf(token))).unwrap();
+//! spec.add_prefix_and_infix(Tk::Minus, PrecedenceLevel::Third, PrecedenceLevel::First, 
+//!     |parser, token, rbp| Ok(Node::branch(token, vec![parser.parse_expr(rbp)?])), 
+//!     |parser, token, lbp, node| Ok(Node::branch(token, vec![node, parser.parse_expr(lbp)?]))
+//! ).unwrap();
+//! 
+//! //-a - b
+//! let tokens = vec![Tk::Minus, Tk::Var('a'), Tk::Minus, Tk::Var('b')];
+//! let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+//! assert_eq!(parser.parse(), Ok(Node::branch(Tk::Minus, vec![
+//!     Node::branch(Tk::Minus, vec![Node::leaf(Tk::Var('a'))]), 
+//!     Node::leaf(Tk::Var('b'))
+//! ])));
+//! # }
+//! ```
+//! 
 //! ## Notes
 //! ParserSpec utilizes a "WriteOnce" pattern with the HashMaps where only the first 
 //! token -> syntax rule is recorded. This means later attempts to reassign the
 //! token -> syntax rule mapping are cause an error. 
 //! 
 
-use std::collections::{HashMap};
+use std::collections::HashMap;
+use std::collections::hash_map::{Entry, RandomState};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
 use std::marker::{Send, Sync};
-use std::mem::{discriminant, Discriminant};
+use std::ops::Deref;
+use std::sync::Arc;
 
-use precedence::PrecedenceLevel;
-use token::Token;
+use errors::ParseError;
+use node::Node;
+use parser::Parser;
+use precedence::{Associativity, PrecedenceLevel};
+use token::{DiscriminantKey, KeyFn, Token};
 use types::*;
 
 /// This currently only indicates if your specification attempts to assign 
@@ -56,83 +100,846 @@ use types::*;
 #[derive(Clone, Debug, Fail)]
 pub enum SpecificationError<T: Token + Send + Sync + 'static> {
     #[fail(display = "{} token -> rule mapping was already defined", tk)]
-    TokenToRuleAlreadyDefined{tk: T}
+    TokenToRuleAlreadyDefined{tk: T}, 
+    #[fail(display = "no rule is defined for {}", tk)]
+    RuleNotDefined{tk: T}
 }
 
+type ExactLookup<T, V> = for<'a, 'b> fn(&'a HashMap<T, V>, &'b T) -> Option<&'a V>;
+
+/// Rules keyed on the whole token instead of its KeyFn key, so that 
+/// `Keyword("if")` and `Keyword("while")` can have different rules even 
+/// though they share a variant. 
+/// 
+/// Looking tokens up needs `T: Eq + Hash`, which the parser doesn't otherwise 
+/// require, so the lookup function is captured when the first rule is 
+/// inserted (the only place the bound is needed). Until then a lookup is a 
+/// single branch, so grammars that don't use exact rules pay next to nothing. 
 #[derive(Clone)]
-pub struct ParserSpec<T: Token + Send + Sync + 'static> {
-    null_map: HashMap<Discriminant<T>, NullInfo<T>>, 
-    left_map: HashMap<Discriminant<T>, LeftInfo<T>>,
+pub struct ExactMap<T, V> {
+    map: HashMap<T, V>, 
+    lookup: Option<ExactLookup<T, V>>, 
+}
+
+impl<T, V> ExactMap<T, V> {
+    pub fn new() -> ExactMap<T, V> {
+        ExactMap {
+            map: HashMap::new(), 
+            lookup: None, 
+        }
+    }
+
+    ///The value registered for exactly `token`, if any. 
+    pub fn get(&self, token: &T) -> Option<&V> {
+        self.lookup.and_then(|lookup| lookup(&self.map, token))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    ///Inserts a value for `token`, handing the token back if it already has one. 
+    fn insert(&mut self, token: T, value: V) -> Result<(), T> where T: Eq + Hash {
+        if self.map.contains_key(&token) {
+            return Err(token)
+        }
+        self.lookup = Some(exact_lookup::<T, V>);
+        self.map.insert(token, value);
+        Ok(())
+    }
+}
+
+impl<T, V> Default for ExactMap<T, V> {
+    fn default() -> ExactMap<T, V> {
+        ExactMap::new()
+    }
+}
+
+fn exact_lookup<'a, T: Eq + Hash, V>(map: &'a HashMap<T, V>, token: &T) -> Option<&'a V> {
+    map.get(token)
+}
+
+/// The second generic, `K`, picks how tokens are keyed to their rules 
+/// (see token::KeyFn). It defaults to keying on the enum variant. 
+/// The third, `S`, is the hasher of the rule maps (see with_key_and_hasher), 
+/// std's RandomState by default. 
+#[derive(Clone)]
+pub struct ParserSpec<T: Token + Send + Sync + 'static, K: KeyFn<T> = DiscriminantKey, S: BuildHasher = RandomState> {
+    null_map: NullMap<T, K, S>, 
+    left_map: LeftMap<T, K, S>,
+    key_fn: K,
+    //Tokens as they were registered, in order, so the spec can be printed 
+    // (the maps only hold keys, which needn't be printable). 
+    null_tokens: Vec<T>, 
+    left_tokens: Vec<T>, 
+    default_null: Option<NullInfo<T>>, 
+    default_left: Option<LeftInfo<T>>, 
+    entry: PrecedenceLevel, 
+    exact_null: ExactMap<T, NullInfo<T>>, 
+    exact_left: ExactMap<T, LeftInfo<T>>, 
+    statement_terminator: Option<T>, 
+    closing: Vec<T>, 
+    opening: Vec<T>, 
+    max_nesting: Option<usize>, 
+    delimiters: Vec<(T, T)>, 
+    error_token: Option<T>, 
 }
 
 impl<T: Token + Send + Sync + 'static> ParserSpec<T>
 {
     pub fn new() -> ParserSpec<T> {
+        ParserSpec::with_key(DiscriminantKey)
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>> ParserSpec<T, K>
+{
+    ///Creates a spec whose rules are keyed by `key_fn`, for example 
+    /// ```ParserSpec::with_key(KindKey)``` for KindedToken. 
+    pub fn with_key(key_fn: K) -> ParserSpec<T, K> {
+        ParserSpec::with_key_and_hasher(key_fn, RandomState::new())
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> ParserSpec<T, K, S>
+{
+    ///Like with_key, but the rule maps hash keys with `hasher`, for instance 
+    /// a faster non-cryptographic hasher (the keys aren't attacker controlled 
+    /// unless the grammar is). Parsers built from the spec use it too. 
+    pub fn with_key_and_hasher(key_fn: K, hasher: S) -> ParserSpec<T, K, S> 
+        where S: Clone
+    {
         ParserSpec {
-            null_map: HashMap::new(), 
-            left_map: HashMap::new(),
+            null_map: HashMap::with_hasher(hasher.clone()), 
+            left_map: HashMap::with_hasher(hasher),
+            key_fn,
+            null_tokens: Vec::new(), 
+            left_tokens: Vec::new(), 
+            default_null: None, 
+            default_left: None, 
+            entry: PrecedenceLevel::Root, 
+            exact_null: ExactMap::new(), 
+            exact_left: ExactMap::new(), 
+            statement_terminator: None, 
+            closing: Vec::new(), 
+            opening: Vec::new(), 
+            max_nesting: None, 
+            delimiters: Vec::new(), 
+            error_token: None, 
         }
     }
 
-    pub fn add_null_assoc(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: NullDenotation<T>) -> Result<(), SpecificationError<T>> {
-        let token = token.into();
-        let disc = discriminant(&token);
-        if !self.null_map.contains_key(&disc) {
-            self.null_map.insert(disc, (bp, func));
-            Ok(())
-        } else {
-            Err(SpecificationError::TokenToRuleAlreadyDefined{tk: token})
+    pub fn add_null_assoc(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> {
+        self.insert_null(token.into(), bp, bp, Arc::new(func))
+    }
+
+    ///Registers a prefix operator whose rule is handed `rbp` to parse its 
+    /// operand with, rather than `bp`. The operand's binding power then lives 
+    /// in the spec, where set_prefix_rbp can change it, instead of being 
+    /// hardcoded in the closure: 
+    /// 
+    /// ```rust
+    /// # extern crate prattle;
+    /// # use prattle::prelude::*;
+    /// # use std::fmt;
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// enum Tk { Num(u32), Sub, Mul }
+    /// # impl fmt::Display for Tk {
+    /// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+    /// # }
+    /// 
+    /// # fn main() -> Result<(), SpecificationError<Tk>> {
+    /// let mut spec = ParserSpec::new();
+    /// spec.add_null_assoc(Tk::Num(0), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token)))?;
+    /// spec.add_left_assoc(Tk::Mul, PrecedenceLevel::Second, |parser, token, bp, node| {
+    ///     Ok(Node::branch(token, vec![node, parser.parse_expr(bp)?]))
+    /// })?;
+    /// spec.add_prefix(Tk::Sub, PrecedenceLevel::Root, PrecedenceLevel::Third, |parser, token, rbp| {
+    ///     Ok(Node::Composite{token, children: vec![parser.parse_expr(rbp)?], span: None})
+    /// })?;
+    /// 
+    /// //The operand is parsed at Third, so Mul doesn't bind inside it: (-1) * 2
+    /// let tokens = vec![Tk::Sub, Tk::Num(1), Tk::Mul, Tk::Num(2)];
+    /// let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+    /// assert_eq!(parser.parse(), Ok(Node::branch(Tk::Mul, vec![
+    ///     Node::branch(Tk::Sub, vec![Node::Simple(Tk::Num(1))]), 
+    ///     Node::Simple(Tk::Num(2)), 
+    /// ])));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_prefix(&mut self, token: impl Into<T>, bp: PrecedenceLevel, rbp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> {
+        self.insert_null(token.into(), bp, rbp, Arc::new(func))
+    }
+
+    ///Changes the rbp handed to the null rule registered for `token`. 
+    pub fn set_prefix_rbp(&mut self, token: &T, rbp: PrecedenceLevel) -> Result<(), SpecificationError<T>> {
+        let key = self.key_fn.key(token);
+        match self.null_map.get_mut(&key) {
+            Some(info) => {
+                info.1 = rbp;
+                Ok(())
+            }, 
+            None => Err(SpecificationError::RuleNotDefined{tk: token.clone()})
         }
     }
 
-    pub fn add_left_assoc(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: LeftDenotation<T>) -> Result<(), SpecificationError<T>> {
+    ///Registers both roles of a token that is a prefix operator where an 
+    /// expression starts and an infix one after an operand, like `-`. The 
+    /// prefix rule is handed prefix_bp to parse its operand with, the infix 
+    /// rule infix_bp, as with add_null_assoc and add_left_assoc. Fails without 
+    /// registering either if the token already has one of the rules. 
+    pub fn add_prefix_and_infix(&mut self, token: impl Into<T>, prefix_bp: PrecedenceLevel, infix_bp: PrecedenceLevel, 
+        prefix: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static, 
+        infix: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> 
+    {
         let token = token.into();
-        let disc = discriminant(&token);
-        if !self.left_map.contains_key(&disc) {
-            self.left_map.insert(disc, (bp, bp, func));
-            Ok(())
-        } else {
-            Err(SpecificationError::TokenToRuleAlreadyDefined{tk: token})
+        let key = self.key_fn.key(&token);
+        if self.null_map.contains_key(&key) || self.left_map.contains_key(&key) {
+            return Err(SpecificationError::TokenToRuleAlreadyDefined{tk: token})
         }
+        self.insert_null(token.clone(), prefix_bp, prefix_bp, Arc::new(prefix))?;
+        self.insert_left(token, infix_bp, infix_bp, Arc::new(infix))
     }
 
-    pub fn add_left_right_assoc(&mut self, token: impl Into<T>, lbp: PrecedenceLevel, rbp: PrecedenceLevel, func: LeftDenotation<T>) -> Result<(), SpecificationError<T>> {
-        let token = token.into();
-        let disc = discriminant(&token);
-        if !self.left_map.contains_key(&disc) {
-            self.left_map.insert(disc, (lbp, rbp, func));
-            Ok(())
-        } else {
-            Err(SpecificationError::TokenToRuleAlreadyDefined{tk: token})
+    pub fn add_left_assoc(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> {
+        self.insert_left(token.into(), bp, bp, Arc::new(func))
+    }
+
+    pub fn add_left_right_assoc(&mut self, token: impl Into<T>, lbp: PrecedenceLevel, rbp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> {
+        self.insert_left(token.into(), lbp, rbp, Arc::new(func))
+    }
+
+    ///Registers `token` as an operand that stands for itself, such as an 
+    /// identifier or a literal: its null rule, at PrecedenceLevel::Root, 
+    /// returns ```Node::Simple(token)```. 
+    pub fn add_atom(&mut self, token: impl Into<T>) -> Result<(), SpecificationError<T>> {
+        self.insert_null(token.into(), PrecedenceLevel::Root, PrecedenceLevel::Root, Arc::new(|_: &mut dyn Parser<T>, token, _| {
+            Ok(Node::Simple(token))
+        }))
+    }
+
+    ///add_atom for each of `tokens`. 
+    pub fn add_atoms(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>) -> Result<(), SpecificationError<T>> {
+        for token in tokens {
+            self.add_atom(token)?;
         }
+        Ok(())
     }
 
-    pub fn add_null_associations(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>, bp: PrecedenceLevel, func: NullDenotation<T>) -> Result<(), SpecificationError<T>> {
+    ///Registers `token` as a binary operator at `level`, with a rule that 
+    /// builds ```Composite{token, children: vec![lhs, rhs]}``` itself, for 
+    /// the usual case where that is all the rule would do. The binding 
+    /// powers come from `assoc` (see Associativity::binding_powers). 
+    pub fn add_infix(&mut self, token: impl Into<T>, level: PrecedenceLevel, assoc: Associativity) -> Result<(), SpecificationError<T>> {
+        let (operand, bp) = assoc.binding_powers(level);
+        self.insert_left(token.into(), operand, bp, Arc::new(|parser: &mut dyn Parser<T>, token, operand, lhs| {
+            Ok(Node::Composite{token, children: vec![lhs, parser.parse_expr(operand)?], span: None})
+        }))
+    }
+
+    ///Like add_infix with Associativity::Left, but building one n-ary node 
+    /// per chain of the operator instead of nesting binary ones: 
+    /// ```a + b + c``` becomes ```Composite{token: +, children: [a, b, c]}```. 
+    /// When the left operand is already a node of an equal token, the right 
+    /// one is appended to its children. Brackets that leave nothing in the 
+    /// tree (see add_group) don't stop the flattening, so only use it for 
+    /// associative operators. 
+    pub fn add_flat_infix(&mut self, token: impl Into<T>, level: PrecedenceLevel) -> Result<(), SpecificationError<T>> {
+        self.insert_left(token.into(), level, level, Arc::new(|parser: &mut dyn Parser<T>, token, operand, mut lhs| {
+            let rhs = parser.parse_expr(operand)?;
+            match lhs {
+                Node::Composite{token: ref lhs_token, ref mut children, ..} if *lhs_token == token => {
+                    children.push(rhs);
+                }, 
+                _ => return Ok(Node::Composite{token, children: vec![lhs, rhs], span: None})
+            }
+            Ok(lhs)
+        }))
+    }
+
+    ///Registers a left rule for `token` that dispatches on the shape of the 
+    /// left operand: the first of `matchers` whose predicate accepts the 
+    /// node runs, as for a context-sensitive operator whose meaning depends 
+    /// on what came before it. If no predicate matches, the rule fails with 
+    /// ParseError::MalformedSyntax. 
+    pub fn add_left_assoc_matching(&mut self, token: impl Into<T>, level: PrecedenceLevel, matchers: Vec<NodeMatcher<T>>) -> Result<(), SpecificationError<T>> {
+        self.insert_left(token.into(), level, level, Arc::new(move |parser: &mut dyn Parser<T>, token, operand, lhs| {
+            match matchers.iter().find(|&&(matches, _)| matches(&lhs)) {
+                Some((_, func)) => func(parser, token, operand, lhs), 
+                None => Err(ParseError::MalformedSyntax{node: Box::new(lhs), token})
+            }
+        }))
+    }
+
+    pub fn add_null_associations(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> {
+        let func: NullDenotation<T> = Arc::new(func);
         for token in tokens {
-            self.add_null_assoc(token, bp, func)?;
+            self.insert_null(token.into(), bp, bp, func.clone())?;
         }
         Ok(())
     }
 
-    pub fn add_left_associations(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>, bp: PrecedenceLevel, func: LeftDenotation<T>) -> Result<(), SpecificationError<T>> {
+    pub fn add_left_associations(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> {
+        let func: LeftDenotation<T> = Arc::new(func);
         for token in tokens {
-            self.add_left_assoc(token, bp, func)?;
+            self.insert_left(token.into(), bp, bp, func.clone())?;
         }
         Ok(())
     }
 
-    pub fn add_left_right_associations(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>, lbp: PrecedenceLevel, rbp: PrecedenceLevel, func: LeftDenotation<T>) -> Result<(), SpecificationError<T>>{
+    ///Registers `func` as the null rule (at PrecedenceLevel::Root) of every 
+    /// token in `all_tokens` whose key has no null rule yet, leaving the 
+    /// registered ones alone. While bringing up a large grammar, a filler 
+    /// that returns an error naming its token says which rule is still to 
+    /// be written: 
+    /// 
+    /// ```rust
+    /// # extern crate prattle;
+    /// # use prattle::prelude::*;
+    /// # use std::fmt;
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// enum Tk { Num(u32), Add, Sub }
+    /// # impl fmt::Display for Tk {
+    /// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+    /// # }
+    /// 
+    /// # fn main() -> Result<(), SpecificationError<Tk>> {
+    /// let mut spec = ParserSpec::new();
+    /// spec.add_null_assoc(Tk::Num(0), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token)))?;
+    /// spec.fill_unhandled(&[Tk::Num(0), Tk::Add, Tk::Sub], |_, token, _| {
+    ///     Err(ParseError::MissingRule{token, ty: "unimplemented".into()})
+    /// });
+    /// 
+    /// let mut parser = GeneralParser::new(spec, LexerVec::new(vec![Tk::Sub, Tk::Num(1)]));
+    /// assert_eq!(parser.parse(), Err(ParseError::MissingRule{token: Tk::Sub, ty: "unimplemented".into()}));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn fill_unhandled(&mut self, all_tokens: &[T], func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) {
+        let func: NullDenotation<T> = Arc::new(func);
+        for token in all_tokens {
+            if let Entry::Vacant(entry) = self.null_map.entry(self.key_fn.key(token)) {
+                entry.insert((PrecedenceLevel::Root, PrecedenceLevel::Root, func.clone()));
+                self.null_tokens.push(token.clone());
+            }
+        }
+    }
+
+    pub fn add_left_right_associations(&mut self, tokens: impl IntoIterator<Item=impl Into<T>>, lbp: PrecedenceLevel, rbp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>>{
+        let func: LeftDenotation<T> = Arc::new(func);
         for token in tokens {
-            self.add_left_right_assoc(token, lbp, rbp, func)?;
+            self.insert_left(token.into(), lbp, rbp, func.clone())?;
         }
         Ok(())
     }
 
+    ///Registers a null rule for tokens equal to `token`, rather than for every 
+    /// token with the same key. Exact rules are checked before keyed ones, so 
+    /// `Keyword("if")` can have its own rule next to a general `Keyword` rule. 
+    pub fn add_null_exact(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> 
+        where T: Eq + Hash
+    {
+        let token = token.into();
+        self.exact_null.insert(token.clone(), (bp, bp, Arc::new(func)))
+            .map_err(|tk| SpecificationError::TokenToRuleAlreadyDefined{tk})?;
+        self.null_tokens.push(token);
+        Ok(())
+    }
+
+    ///Registers a left rule for tokens equal to `token`. See add_null_exact. 
+    pub fn add_left_exact(&mut self, token: impl Into<T>, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) -> Result<(), SpecificationError<T>> 
+        where T: Eq + Hash
+    {
+        let token = token.into();
+        self.exact_left.insert(token.clone(), (bp, bp, Arc::new(func)))
+            .map_err(|tk| SpecificationError::TokenToRuleAlreadyDefined{tk})?;
+        self.left_tokens.push(token);
+        Ok(())
+    }
+
+    ///The rules registered with add_null_exact/add_left_exact. Cloning them 
+    /// clones the tokens and bumps the Arc reference counts of the rules. 
+    pub fn exact_maps(&self) -> (ExactMap<T, NullInfo<T>>, ExactMap<T, LeftInfo<T>>) {
+        (self.exact_null.clone(), self.exact_left.clone())
+    }
+
+    ///Sets a catch-all null rule, used for any token without a null rule of 
+    /// its own (instead of failing with MissingRule). Handy for forgiving 
+    /// grammars, e.g. treating every unknown token as an identifier. 
+    /// Setting it again replaces the previous default. 
+    pub fn set_default_null(&mut self, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) {
+        self.default_null = Some((bp, bp, Arc::new(func)));
+    }
+
+    ///Sets a catch-all left rule, used for any token without a left rule of 
+    /// its own. Note this makes *every* such token an infix/postfix operator 
+    /// at `bp`, terminators included, so it only suits grammars where that 
+    /// is intended. Setting it again replaces the previous default. 
+    pub fn set_default_left(&mut self, bp: PrecedenceLevel, func: impl Fn(&mut dyn Parser<T>, T, PrecedenceLevel, Node<T>) -> Result<Node<T>, ParseError<T>> + Send + Sync + 'static) {
+        self.default_left = Some((bp, bp, Arc::new(func)));
+    }
+
+    ///The catch-all rules set with set_default_null/set_default_left. Cloning 
+    /// them only bumps the Arc reference counts. 
+    pub fn defaults(&self) -> (Option<NullInfo<T>>, Option<LeftInfo<T>>) {
+        (self.default_null.clone(), self.default_left.clone())
+    }
+
+    ///Designates the precedence level parse() starts from, for grammars whose 
+    /// canonical entry point isn't PrecedenceLevel::Root (for instance, 
+    /// statements at Root but a REPL that reads bare expressions at First). 
+    /// Defaults to Root. 
+    pub fn set_entry(&mut self, level: PrecedenceLevel) {
+        self.entry = level;
+    }
+
+    ///The entry level set with set_entry. 
+    pub fn entry(&self) -> PrecedenceLevel {
+        self.entry
+    }
+
+    ///Designates the token that ends a top-level statement, such as `;`. 
+    /// GeneralParser::parse_program then consumes it after each statement, 
+    /// so statement lists need no rule for it. 
+    pub fn set_statement_terminator(&mut self, token: impl Into<T>) {
+        self.statement_terminator = Some(token.into());
+    }
+
+    ///The terminator set with set_statement_terminator. 
+    pub fn statement_terminator(&self) -> Option<&T> {
+        self.statement_terminator.as_ref()
+    }
+
+    ///Designates the token a lexer emits for input it couldn't tokenize. 
+    /// Starting an expression at it is ParseError::LexicalError rather than 
+    /// a MissingRule, and it never binds as an operator; in recovery mode 
+    /// (see GeneralParser::set_error_recovery) it is skipped, leaving a 
+    /// Node::Error in its place. Error tokens are matched by key, so one 
+    /// carrying the offending text matches whatever the text. 
+    pub fn set_error_token(&mut self, token: impl Into<T>) {
+        self.error_token = Some(token.into());
+    }
+
+    ///The token set with set_error_token. 
+    pub fn error_token(&self) -> Option<&T> {
+        self.error_token.as_ref()
+    }
+
+    ///Whether `token` has the key of the token set with set_error_token. 
+    pub fn is_error_token(&self, token: &T) -> bool {
+        self.error_token.as_ref().is_some_and(|error| self.key_fn.key(error) == self.key_fn.key(token))
+    }
+
+    ///Registers a closing token, such as `)` or `]`: one that always ends 
+    /// the expression before it, even if it has a left rule (or a default 
+    /// left rule applies), and that can't start one. Starting an expression 
+    /// at it is ParseError::UnexpectedClosing rather than a MissingRule. 
+    /// Closing tokens are matched by equality, not by key. 
+    pub fn add_closing(&mut self, token: impl Into<T>) {
+        self.closing.push(token.into());
+    }
+
+    ///Whether `token` was registered with add_closing. 
+    pub fn is_closing(&self, token: &T) -> bool {
+        self.closing.contains(token)
+    }
+
+    ///Registers a bracket pair: a null rule for `open` that parses the 
+    /// expression inside and consumes `close`, giving back the inner node 
+    /// (brackets only group, they leave nothing in the tree). `close` is 
+    /// registered as closing, and `open` as opening, so it counts towards 
+    /// the nesting limit (see set_max_nesting). The two are also a delimiter 
+    /// pair (see add_delimiters), so a wrong closer is MismatchedDelimiter. 
+    pub fn add_group(&mut self, open: impl Into<T>, close: impl Into<T>) -> Result<(), SpecificationError<T>> {
+        let (open, close) = (open.into(), close.into());
+        //The rule first, so a duplicate leaves the other tables alone
+        self.insert_null(open.clone(), PrecedenceLevel::Root, PrecedenceLevel::Root, Arc::new(|parser: &mut dyn Parser<T>, open, rbp| {
+            let inner = parser.parse_expr(rbp)?;
+            parser.consume_matching(&open)?;
+            Ok(inner)
+        }))?;
+        self.add_closing(close.clone());
+        self.add_opening(open.clone());
+        self.add_delimiters(open, close);
+        Ok(())
+    }
+
+    ///Registers `close` as the token that matches `open`, such as `}` for 
+    /// `{`, for Parser::consume_matching. Pairs are matched by equality; a 
+    /// later pair for the same `open` replaces the earlier one. 
+    pub fn add_delimiters(&mut self, open: impl Into<T>, close: impl Into<T>) {
+        let (open, close) = (open.into(), close.into());
+        self.delimiters.retain(|(registered, _)| *registered != open);
+        self.delimiters.push((open, close));
+    }
+
+    ///The close token registered for `open` with add_delimiters. 
+    pub fn closing_for(&self, open: &T) -> Option<&T> {
+        self.delimiters.iter()
+            .find(|(registered, _)| registered == open)
+            .map(|(_, close)| close)
+    }
+
+    ///Registers an opening token, such as `(` or `[`, whose null rule is 
+    /// written by hand rather than with add_group, so its nesting is counted 
+    /// against the limit all the same. Matched by equality, like closing 
+    /// tokens. 
+    pub fn add_opening(&mut self, token: impl Into<T>) {
+        self.opening.push(token.into());
+    }
+
+    ///Whether `token` was registered with add_opening or add_group. 
+    pub fn is_opening(&self, token: &T) -> bool {
+        self.opening.contains(token)
+    }
+
+    ///Limits how deeply opening tokens may nest: parsing the null rule of an 
+    /// opening token while `limit` of them are already open is 
+    /// ParseError::NestingTooDeep. This guards against inputs like 
+    /// ```((((((...))))))```, which nest without any operator recursion. 
+    /// Unlimited by default. 
+    pub fn set_max_nesting(&mut self, limit: usize) {
+        self.max_nesting = Some(limit);
+    }
+
+    ///The limit set with set_max_nesting. 
+    pub fn max_nesting(&self) -> Option<usize> {
+        self.max_nesting
+    }
+
+    fn insert_null(&mut self, token: T, bp: PrecedenceLevel, rbp: PrecedenceLevel, func: NullDenotation<T>) -> Result<(), SpecificationError<T>> {
+        match self.null_map.entry(self.key_fn.key(&token)) {
+            Entry::Vacant(entry) => {
+                entry.insert((bp, rbp, func));
+                self.null_tokens.push(token);
+                Ok(())
+            }, 
+            Entry::Occupied(_) => Err(SpecificationError::TokenToRuleAlreadyDefined{tk: token})
+        }
+    }
+
+    fn insert_left(&mut self, token: T, lbp: PrecedenceLevel, rbp: PrecedenceLevel, func: LeftDenotation<T>) -> Result<(), SpecificationError<T>> {
+        match self.left_map.entry(self.key_fn.key(&token)) {
+            Entry::Vacant(entry) => {
+                entry.insert((lbp, rbp, func));
+                self.left_tokens.push(token);
+                Ok(())
+            }, 
+            Entry::Occupied(_) => Err(SpecificationError::TokenToRuleAlreadyDefined{tk: token})
+        }
+    }
+
     ///Consumes a spec and gets the HashMaps used for mapping tokens
     /// to syntax rules. This avoids clones and allocations/deallocations 
     /// of potentially large HashMaps when creating a Parser from the maps.
-    pub fn maps(self) -> (HashMap<Discriminant<T>, NullInfo<T>>, HashMap<Discriminant<T>, LeftInfo<T>>) {
-        return (self.null_map, self.left_map)
+    pub fn maps(self) -> (NullMap<T, K, S>, LeftMap<T, K, S>) {
+        (self.null_map, self.left_map)
+    }
+
+    ///The keyed null rules, borrowed, for custom Parser implementations that 
+    /// look rules up themselves (maps() hands them over but consumes the spec). 
+    /// Keys come from key_fn(). A parser that folds a token list by binding 
+    /// power alone might read them like so: 
+    /// 
+    /// ```rust
+    /// # extern crate prattle;
+    /// # use std::fmt;
+    /// # use std::iter::Peekable;
+    /// # use std::vec::IntoIter;
+    /// use prattle::prelude::*;
+    /// use prattle::token::KeyFn;
+    /// 
+    /// #[derive(Clone, Debug, PartialEq)]
+    /// enum Tk { Num(u32), Add, Mul }
+    /// # impl fmt::Display for Tk {
+    /// #     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{:?}", self) }
+    /// # }
+    /// 
+    /// fn climb(spec: &ParserSpec<Tk>, tokens: &mut Peekable<IntoIter<Tk>>, rbp: PrecedenceLevel) -> Node<Tk> {
+    ///     let operand = tokens.next().unwrap();
+    ///     assert!(spec.null_map().contains_key(&spec.key_fn().key(&operand)));
+    ///     let mut lhs = Node::Simple(operand);
+    ///     while let Some(&(lbp, _, _)) = tokens.peek().and_then(|tk| spec.left_map().get(&spec.key_fn().key(tk))) {
+    ///         if lbp <= rbp {
+    ///             break
+    ///         }
+    ///         let token = tokens.next().unwrap();
+    ///         let rhs = climb(spec, tokens, lbp);
+    ///         lhs = Node::Composite{token, children: vec![lhs, rhs], span: None};
+    ///     }
+    ///     lhs
+    /// }
+    /// 
+    /// # fn main() {
+    /// let mut spec = ParserSpec::new();
+    /// spec.add_null_assoc(Tk::Num(0), PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+    /// spec.add_left_assoc(Tk::Add, PrecedenceLevel::First, |parser, token, lbp, node| {
+    ///     Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    /// }).unwrap();
+    /// spec.add_left_assoc(Tk::Mul, PrecedenceLevel::Second, |parser, token, lbp, node| {
+    ///     Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+    /// }).unwrap();
+    /// 
+    /// let mut tokens = vec![Tk::Num(1), Tk::Add, Tk::Num(2), Tk::Mul, Tk::Num(3)].into_iter().peekable();
+    /// let tree = climb(&spec, &mut tokens, PrecedenceLevel::Root);
+    /// assert_eq!(tree, Node::Composite{token: Tk::Add, children: vec![
+    ///     Node::Simple(Tk::Num(1)), 
+    ///     Node::Composite{token: Tk::Mul, children: vec![Node::Simple(Tk::Num(2)), Node::Simple(Tk::Num(3))], span: None}
+    /// ], span: None});
+    /// # }
+    /// ```
+    pub fn null_map(&self) -> &NullMap<T, K, S> {
+        &self.null_map
+    }
+
+    ///The keyed left rules, borrowed. See null_map. 
+    pub fn left_map(&self) -> &LeftMap<T, K, S> {
+        &self.left_map
+    }
+
+    ///Tokens with a left rule but no null rule, in registration order. Such a 
+    /// token can never start an expression, which is usually a forgotten null 
+    /// rule (a `-` registered for subtraction but not negation, say). Empty 
+    /// when a default null rule is set, since that covers every token. 
+    pub fn orphan_left_rules(&self) -> Vec<T> {
+        if self.default_null.is_some() {
+            return Vec::new()
+        }
+        self.left_tokens.iter()
+            .filter(|token| self.exact_null.get(token).is_none() 
+                && !self.null_map.contains_key(&self.key_fn.key(token)))
+            .cloned()
+            .collect()
+    }
+
+    ///Which left rules can ever run, judging by binding powers alone (see 
+    /// ReachabilityReport). A left rule runs when its lbp is above the rbp 
+    /// of the expression it follows. Expressions are parsed at the entry 
+    /// level, at the rbp of every null rule, and at the operand level of 
+    /// every reachable left rule, so the lowest of these, the floor, decides: 
+    /// operators at or below it never bind, and neither do closing tokens. 
+    /// 
+    /// Rules are assumed to recurse at the level they're handed; one that 
+    /// calls parse_expr with a level of its own can reach operators this 
+    /// reports as unreachable. A precedence table set on the parser isn't 
+    /// taken into account either. 
+    pub fn reachability_report(&self) -> ReachabilityReport<T> {
+        let mut report = ReachabilityReport{reachable: Vec::new(), unreachable: Vec::new(), floor: self.entry};
+        let starts = self.null_rules().into_iter().map(|rule| rule.2)
+            .chain(self.default_null.as_ref().map(|info| info.1))
+            .collect::<Vec<_>>();
+        if starts.is_empty() {
+            //Nothing can start an expression, so nothing can follow one
+            report.unreachable = self.left_tokens.clone();
+            return report
+        }
+        report.floor = starts.into_iter().fold(self.entry, ::std::cmp::min);
+        let rules = self.left_tokens.iter()
+            .filter(|token| !self.is_closing(token))
+            .filter_map(|token| self.left_rule(token).map(|info| (info.0, info.1)))
+            .collect::<Vec<_>>();
+        //Reachable operators can lower the floor for the ones below them
+        loop {
+            let floor = rules.iter()
+                .filter(|&&(_, lbp)| lbp > report.floor)
+                .map(|&(operand_bp, _)| operand_bp)
+                .fold(report.floor, ::std::cmp::min);
+            if floor == report.floor {
+                break
+            }
+            report.floor = floor;
+        }
+        for token in &self.left_tokens {
+            let lbp = self.left_rule(token).map(|info| info.1);
+            if !self.is_closing(token) && lbp.is_some_and(|lbp| lbp > report.floor) {
+                report.reachable.push(token.clone());
+            } else {
+                report.unreachable.push(token.clone());
+            }
+        }
+        report
+    }
+
+    ///The binding power of the null rule registered for `token`, so rules can 
+    /// refer to "the level of operator X" instead of hardcoding it. Exact 
+    /// rules are checked before keyed ones, as the parser does; the default 
+    /// null rule isn't consulted. 
+    pub fn null_bp(&self, token: &T) -> Option<PrecedenceLevel> {
+        self.null_rule(token).map(|info| info.0)
+    }
+
+    ///The (lbp, rbp) of the left rule registered for `token`. See null_bp. 
+    pub fn left_bp(&self, token: &T) -> Option<(PrecedenceLevel, PrecedenceLevel)> {
+        self.left_rule(token).map(|info| (info.0, info.1))
+    }
+
+    ///The null rule registered for `token`: its exact rule, else its keyed 
+    /// one. The default rule is left to the caller. 
+    pub(crate) fn null_rule(&self, token: &T) -> Option<&NullInfo<T>> {
+        self.exact_null.get(token)
+            .or_else(|| self.null_map.get(&self.key_fn.key(token)))
+    }
+
+    ///The left rule registered for `token`. See null_rule. 
+    pub(crate) fn left_rule(&self, token: &T) -> Option<&LeftInfo<T>> {
+        self.exact_left.get(token)
+            .or_else(|| self.left_map.get(&self.key_fn.key(token)))
+    }
+
+    ///The catch-all rules, borrowed. 
+    pub(crate) fn default_rules(&self) -> (Option<&NullInfo<T>>, Option<&LeftInfo<T>>) {
+        (self.default_null.as_ref(), self.default_left.as_ref())
+    }
+
+    ///Moves the spec behind an Arc, once it's complete, so that it can be 
+    /// shared by any number of parsers without copying its rules. 
+    pub fn freeze(self) -> FrozenSpec<T, K, S> {
+        FrozenSpec{spec: Arc::new(self)}
+    }
+
+    ///The KeyFn used to key this spec's rules. 
+    pub fn key_fn(&self) -> &K {
+        &self.key_fn
+    }
+
+    ///Registered null rules as (token, bp, rbp), ordered by binding power. 
+    fn null_rules(&self) -> Vec<(&T, PrecedenceLevel, PrecedenceLevel)> {
+        let mut rules: Vec<_> = self.null_tokens.iter()
+            .map(|token| {
+                let info = self.exact_null.get(token).unwrap_or_else(|| &self.null_map[&self.key_fn.key(token)]);
+                (token, info.0, info.1)
+            })
+            .collect();
+        rules.sort_by_key(|rule| (rule.1, rule.2));
+        rules
+    }
+
+    ///Registered left rules as (token, lbp, rbp), ordered by binding power. 
+    fn left_rules(&self) -> Vec<(&T, PrecedenceLevel, PrecedenceLevel)> {
+        let mut rules: Vec<_> = self.left_tokens.iter()
+            .map(|token| {
+                let info = self.exact_left.get(token).unwrap_or_else(|| &self.left_map[&self.key_fn.key(token)]);
+                (token, info.0, info.1)
+            })
+            .collect();
+        rules.sort_by_key(|rule| (rule.1, rule.2));
+        rules
+    }
+}
+
+///Lists each registered token with its role and binding power, lowest 
+/// precedence first. 
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> fmt::Display for ParserSpec<T, K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "ParserSpec:")?;
+        for (token, bp, rbp) in self.null_rules() {
+            if bp == rbp {
+                writeln!(f, "    null {}: bp {}", token, u32::from(bp))?;
+            } else {
+                writeln!(f, "    null {}: bp {}, rbp {}", token, u32::from(bp), u32::from(rbp))?;
+            }
+        }
+        for (token, lbp, rbp) in self.left_rules() {
+            writeln!(f, "    left {}: lbp {}, rbp {}", token, u32::from(lbp), u32::from(rbp))?;
+        }
+        if let Some((bp, _, _)) = self.default_null {
+            writeln!(f, "    null (default): bp {}", u32::from(bp))?;
+        }
+        if let Some((lbp, rbp, _)) = self.default_left {
+            writeln!(f, "    left (default): lbp {}, rbp {}", u32::from(lbp), u32::from(rbp))?;
+        }
+        Ok(())
+    }
+}
+
+///Like Display, but with the tokens' Debug forms. The rules themselves are 
+/// closures and can't be shown. 
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> fmt::Debug for ParserSpec<T, K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParserSpec")
+            .field("null", &self.null_rules())
+            .field("left", &self.left_rules())
+            .finish()
+    }
+}
+
+impl<T: Token + Send + Sync + 'static> Default for ParserSpec<T> {
+    fn default() -> ParserSpec<T> {
+        ParserSpec::new()
+    }
+}
+
+/// The result of ParserSpec::reachability_report: the tokens with left 
+/// rules, in registration order, split by whether their rule can ever run. 
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ReachabilityReport<T> {
+    /// Left rule tokens that bind above the floor. 
+    pub reachable: Vec<T>, 
+    /// Left rule tokens that never bind: dead rules, most likely registered 
+    /// at the wrong level. 
+    pub unreachable: Vec<T>, 
+    /// The lowest rbp any expression is parsed at. 
+    pub floor: PrecedenceLevel, 
+}
+
+/// A finished ParserSpec behind an Arc (see ParserSpec::freeze). Cloning 
+/// one only bumps the reference count, however many rules the spec holds, 
+/// so spawning many parsers from one spec doesn't copy its maps. Derefs 
+/// to the spec for queries such as null_bp. 
+pub struct FrozenSpec<T: Token + Send + Sync + 'static, K: KeyFn<T> = DiscriminantKey, S: BuildHasher = RandomState> {
+    spec: Arc<ParserSpec<T, K, S>>
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> Clone for FrozenSpec<T, K, S> {
+    fn clone(&self) -> FrozenSpec<T, K, S> {
+        FrozenSpec{spec: Arc::clone(&self.spec)}
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> Deref for FrozenSpec<T, K, S> {
+    type Target = ParserSpec<T, K, S>;
+
+    fn deref(&self) -> &ParserSpec<T, K, S> {
+        &self.spec
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> fmt::Debug for FrozenSpec<T, K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&*self.spec, f)
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> From<ParserSpec<T, K, S>> for FrozenSpec<T, K, S> {
+    fn from(spec: ParserSpec<T, K, S>) -> FrozenSpec<T, K, S> {
+        spec.freeze()
+    }
+}
+
+/// What GeneralParser::new accepts as a spec: a ParserSpec, which is frozen 
+/// for the parser, or an already FrozenSpec, which is shared. 
+pub trait IntoFrozenSpec<T: Token + Send + Sync + 'static> {
+    type Key: KeyFn<T>;
+    type Hasher: BuildHasher;
+    fn into_frozen(self) -> FrozenSpec<T, Self::Key, Self::Hasher>;
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> IntoFrozenSpec<T> for ParserSpec<T, K, S> {
+    type Key = K;
+    type Hasher = S;
+
+    fn into_frozen(self) -> FrozenSpec<T, K, S> {
+        self.freeze()
+    }
+}
+
+impl<T: Token + Send + Sync + 'static, K: KeyFn<T>, S: BuildHasher> IntoFrozenSpec<T> for FrozenSpec<T, K, S> {
+    type Key = K;
+    type Hasher = S;
+
+    fn into_frozen(self) -> FrozenSpec<T, K, S> {
+        self
     }
 }
 
@@ -151,4 +958,486 @@ mod test {
         fn assert_sync<T: Sync>() {}
         assert_sync::<ParserSpec<String>>();
     }
+
+    #[test]
+    fn test_clone_spec_with_captured_state() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::thread;
+
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let calls = Arc::new(AtomicUsize::new(0));
+        let prefix = "ident:".to_string();
+        let mut spec: ParserSpec<String> = ParserSpec::new();
+        {
+            let calls = calls.clone();
+            spec.add_null_assoc("".to_string(), PrecedenceLevel::Root, move |_, token, _| {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Ok(Node::Simple(format!("{}{}", prefix, token)))
+            }).unwrap();
+        }
+
+        let other = spec.clone();
+        let handle = thread::spawn(move || {
+            GeneralParser::new(other, LexerVec::new(vec!["b".to_string()])).parse()
+        });
+        let res = GeneralParser::new(spec, LexerVec::new(vec!["a".to_string()])).parse();
+
+        assert_eq!(res, Ok(Node::Simple("ident:a".to_string())));
+        assert_eq!(handle.join().unwrap(), Ok(Node::Simple("ident:b".to_string())));
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_display_lists_rules_in_precedence_order() {
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_left_assoc("*", PrecedenceLevel::Second, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_left_right_assoc("^", PrecedenceLevel::Second, PrecedenceLevel::Third, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_left_assoc("+", PrecedenceLevel::First, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_prefix("-", PrecedenceLevel::Root, PrecedenceLevel::Third, |_, token, _| Ok(Node::Simple(token))).unwrap();
+
+        assert_eq!(spec.to_string(), "ParserSpec:\n\
+            \x20   null x: bp 0\n\
+            \x20   null -: bp 0, rbp 15\n\
+            \x20   left +: lbp 5, rbp 5\n\
+            \x20   left *: lbp 10, rbp 10\n\
+            \x20   left ^: lbp 10, rbp 15\n");
+        let debug = format!("{:?}", spec);
+        for op in &["\"x\"", "\"+\"", "\"*\"", "\"^\""] {
+            assert!(debug.contains(op), "{} missing from {}", op, debug);
+        }
+    }
+
+    #[test]
+    fn test_prefix_rbp_changes_grouping() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        for name in &["a", "b"] {
+            spec.add_null_assoc(*name, PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        }
+        spec.add_left_assoc("*", PrecedenceLevel::Second, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        spec.add_prefix("-", PrecedenceLevel::Root, PrecedenceLevel::Third, |parser, token, rbp| {
+            Ok(Node::Composite{token, children: vec![parser.parse_expr(rbp)?], span: None})
+        }).unwrap();
+        let parse = |spec: &ParserSpec<String, TextKey>| {
+            let tokens = vec!["-", "a", "*", "b"].into_iter().map(String::from);
+            GeneralParser::new(spec.clone(), LexerVec::new(tokens)).parse().unwrap()
+        };
+        let leaf = |s: &str| Node::Simple(s.to_string());
+        let node = |s: &str, children| Node::Composite{token: s.to_string(), children, span: None};
+
+        //Binding tighter than * : (-a) * b
+        assert_eq!(parse(&spec), node("*", vec![node("-", vec![leaf("a")]), leaf("b")]));
+        //Looser than * : -(a * b)
+        spec.set_prefix_rbp(&"-".to_string(), PrecedenceLevel::First).unwrap();
+        assert_eq!(parse(&spec), node("-", vec![node("*", vec![leaf("a"), leaf("b")])]));
+
+        match spec.set_prefix_rbp(&"!".to_string(), PrecedenceLevel::First) {
+            Err(SpecificationError::RuleNotDefined{tk}) => assert_eq!(tk, "!"), 
+            other => panic!("expected RuleNotDefined, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_prefix_and_infix_minus() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        for name in &["a", "b"] {
+            spec.add_null_assoc(*name, PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        }
+        spec.add_prefix_and_infix("-", PrecedenceLevel::Third, PrecedenceLevel::First, 
+            |parser, token, rbp| Ok(Node::Composite{token, children: vec![parser.parse_expr(rbp)?], span: None}), 
+            |parser, token, lbp, node| Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        ).unwrap();
+        assert!(spec.add_prefix_and_infix("-", PrecedenceLevel::Third, PrecedenceLevel::First, 
+            |_, token, _| Ok(Node::Simple(token)), |_, _, _, node| Ok(node)).is_err());
+
+        let tokens = vec!["-", "a", "-", "b"].into_iter().map(String::from);
+        let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+        let leaf = |s: &str| Node::Simple(s.to_string());
+        assert_eq!(parser.parse(), Ok(Node::Composite{token: "-".to_string(), children: vec![
+            Node::Composite{token: "-".to_string(), children: vec![leaf("a")], span: None}, 
+            leaf("b")
+        ], span: None}));
+    }
+
+    #[test]
+    fn test_mixed_associativity_at_one_level() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        for name in &["a", "b", "c", "d"] {
+            spec.add_null_assoc(*name, PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        }
+        let binary = |parser: &mut dyn Parser<String>, token, lbp, node| Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None});
+        //Both bind at Second; only `/` recurses below it
+        spec.add_left_assoc("*", PrecedenceLevel::Second, binary).unwrap();
+        spec.add_left_right_assoc("/", PrecedenceLevel::First, PrecedenceLevel::Second, binary).unwrap();
+        let spec = spec.freeze();
+        let parse = |src: &str| {
+            let tokens = src.split_whitespace().map(String::from);
+            GeneralParser::new(spec.clone(), LexerVec::new(tokens)).parse().map(|node| node.to_sexpr())
+        };
+        assert_eq!(parse("a * b * c"), Ok("(* (* a b) c)".to_string()));
+        assert_eq!(parse("a / b / c"), Ok("(/ a (/ b c))".to_string()));
+        assert_eq!(parse("a * b / c * d"), Ok("(/ (* a b) (* c d))".to_string()));
+        assert_eq!(parse("a / b * c"), Ok("(/ a (* b c))".to_string()));
+    }
+
+    #[test]
+    fn test_add_infix_arithmetic() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_atoms(vec!["a", "b", "c", "d"]).unwrap();
+        spec.add_infix("+", PrecedenceLevel::First, Associativity::Left).unwrap();
+        spec.add_infix("-", PrecedenceLevel::First, Associativity::Left).unwrap();
+        spec.add_infix("*", PrecedenceLevel::Second, Associativity::Left).unwrap();
+        spec.add_infix("/", PrecedenceLevel::Second, Associativity::Left).unwrap();
+        spec.add_infix("^", PrecedenceLevel::Third, Associativity::Right).unwrap();
+        assert!(spec.add_infix("+", PrecedenceLevel::Second, Associativity::Left).is_err());
+        let spec = spec.freeze();
+        let parse = |src: &str| {
+            let tokens = src.split_whitespace().map(String::from);
+            GeneralParser::new(spec.clone(), LexerVec::new(tokens)).parse().map(|node| node.to_sexpr())
+        };
+        assert_eq!(parse("a - b - c"), Ok("(- (- a b) c)".to_string()));
+        assert_eq!(parse("a + b * c / d"), Ok("(+ a (/ (* b c) d))".to_string()));
+        assert_eq!(parse("a ^ b ^ c * d"), Ok("(* (^ a (^ b c)) d)".to_string()));
+    }
+
+    #[test]
+    fn test_add_flat_infix() {
+        use lexer::{LexerVec, Span};
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_atoms(vec!["a", "b", "c", "d"]).unwrap();
+        spec.add_flat_infix("+", PrecedenceLevel::First).unwrap();
+        spec.add_flat_infix("*", PrecedenceLevel::Second).unwrap();
+        spec.add_infix("-", PrecedenceLevel::First, Associativity::Left).unwrap();
+        let spec = spec.freeze();
+        let parse = |src: &str, spanned: bool| {
+            let tokens = src.split_whitespace().enumerate()
+                .map(|(i, word)| (word.to_string(), Span{start: i, end: i + 1, line: 1, column: i + 1}))
+                .collect();
+            let mut parser = GeneralParser::new(spec.clone(), LexerVec::from_tokens_with_spans(tokens));
+            parser.set_spanned(spanned);
+            let tree = parser.parse()?;
+            assert_eq!(tree.span().is_some(), spanned);
+            Ok::<_, ParseError<String>>(tree.strip_spans())
+        };
+        let leaf = |name: &str| Node::Simple(name.to_string());
+        let flat = Node::Composite{token: "+".to_string(), children: vec![leaf("a"), leaf("b"), leaf("c")], span: None};
+        assert_eq!(parse("a + b + c", false), Ok(flat.clone()));
+        assert_eq!(parse("a + b + c", true), Ok(flat));
+        assert_eq!(parse("a + b * c * d + a", false).map(|node| node.to_sexpr()), Ok("(+ a (* b c d) a)".to_string()));
+        assert_eq!(parse("a + b - c + d", false).map(|node| node.to_sexpr()), Ok("(+ (- (+ a b) c) d)".to_string()));
+    }
+
+    #[test]
+    fn test_add_left_assoc_matching() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        //`~` starts a list after an atom and extends one after a list
+        let is_atom: fn(&Node<String>) -> bool = |node| node.token().is_some() && node.children().is_empty();
+        let is_list: fn(&Node<String>) -> bool = |node| node.token().is_some_and(|token| token == "~");
+        let start: LeftDenotation<String> = Arc::new(|parser, token, operand, lhs| {
+            Ok(Node::Composite{token, children: vec![lhs, parser.parse_expr(operand)?], span: None})
+        });
+        let extend: LeftDenotation<String> = Arc::new(|parser, _, operand, lhs| {
+            let (token, mut children) = match lhs {
+                Node::Composite{token, children, ..} => (token, children), 
+                _ => unreachable!()
+            };
+            children.push(parser.parse_expr(operand)?);
+            Ok(Node::Composite{token, children, span: None})
+        });
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_atoms(vec!["a", "b", "c"]).unwrap();
+        spec.add_infix("+", PrecedenceLevel::Second, Associativity::Left).unwrap();
+        spec.add_left_assoc_matching("~", PrecedenceLevel::First, vec![(is_atom, start), (is_list, extend)]).unwrap();
+        let spec = spec.freeze();
+        let parse = |src: &str| {
+            let tokens = src.split_whitespace().map(String::from);
+            GeneralParser::new(spec.clone(), LexerVec::new(tokens)).parse().map(|node| node.to_sexpr())
+        };
+        assert_eq!(parse("a ~ b"), Ok("(~ a b)".to_string()));
+        assert_eq!(parse("a ~ b ~ c"), Ok("(~ a b c)".to_string()));
+        //A sum is neither
+        match parse("a + b ~ c") {
+            Err(ParseError::MalformedSyntax{token, ..}) => assert_eq!(token, "~"), 
+            other => panic!("expected MalformedSyntax, got {:?}", other)
+        }
+    }
+
+    #[test]
+    fn test_add_atoms() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_atoms(vec!["x", "y"]).unwrap();
+        spec.add_atom("1").unwrap();
+        assert_eq!(spec.null_bp(&"y".to_string()), Some(PrecedenceLevel::Root));
+        assert!(spec.add_atom("x").is_err());
+
+        let mut parser = GeneralParser::new(spec, LexerVec::new(vec!["y".to_string()]));
+        assert_eq!(parser.parse(), Ok(Node::Simple("y".to_string())));
+    }
+
+    #[test]
+    fn test_orphan_left_rules() {
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc("+", PrecedenceLevel::First, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_left_assoc("-", PrecedenceLevel::First, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_null_assoc("-", PrecedenceLevel::Third, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc("*", PrecedenceLevel::Second, |_, _, _, node| Ok(node)).unwrap();
+        assert_eq!(spec.orphan_left_rules(), vec!["+".to_string(), "*".to_string()]);
+
+        spec.set_default_null(PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token)));
+        assert!(spec.orphan_left_rules().is_empty());
+    }
+
+    #[test]
+    fn test_reachability_report() {
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc("+", PrecedenceLevel::First, |_, _, _, node| Ok(node)).unwrap();
+        //Deliberately dead: nothing is ever parsed below Root
+        spec.add_left_assoc("=", PrecedenceLevel::Root, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_left_assoc(")", PrecedenceLevel::Eighth, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_closing(")");
+        let report = spec.reachability_report();
+        assert_eq!(report.reachable, vec!["+".to_string()]);
+        assert_eq!(report.unreachable, vec!["=".to_string(), ")".to_string()]);
+        assert_eq!(report.floor, PrecedenceLevel::Root);
+    }
+
+    #[test]
+    fn test_reachability_follows_operand_levels() {
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.set_entry(PrecedenceLevel::Third);
+        spec.add_null_assoc("x", PrecedenceLevel::Fourth, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc("+", PrecedenceLevel::First, |_, _, _, node| Ok(node)).unwrap();
+        spec.add_left_assoc("*", PrecedenceLevel::Second, |_, _, _, node| Ok(node)).unwrap();
+        //Binds above the entry level, but recurses below it
+        spec.add_left_right_assoc("^", PrecedenceLevel::Second, PrecedenceLevel::Fourth, |_, _, _, node| Ok(node)).unwrap();
+        let report = spec.reachability_report();
+        assert_eq!(report.reachable, vec!["^".to_string()]);
+        assert_eq!(report.unreachable, vec!["+".to_string(), "*".to_string()]);
+        assert_eq!(report.floor, PrecedenceLevel::Second);
+
+        //A prefix parsing its operand at Root reaches everything
+        spec.add_prefix("(", PrecedenceLevel::Root, PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        let report = spec.reachability_report();
+        assert_eq!(report.reachable.len(), 3);
+        assert!(report.unreachable.is_empty());
+    }
+
+    #[test]
+    fn test_fill_unhandled_skips_registered_tokens() {
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_prefix("-", PrecedenceLevel::Second, PrecedenceLevel::Third, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        let tokens: Vec<String> = vec!["x".into(), "-".into(), "y".into(), "(".into()];
+        spec.fill_unhandled(&tokens, |_, token, _| Err(ParseError::MissingRule{token, ty: "unimplemented".into()}));
+
+        assert_eq!(spec.null_map().len(), 4);
+        assert_eq!(spec.null_bp(&"-".to_string()), Some(PrecedenceLevel::Second));
+        assert_eq!(spec.null_bp(&"y".to_string()), Some(PrecedenceLevel::Root));
+
+        let spec = spec.freeze();
+        let parse = |src: &str| {
+            use lexer::LexerVec;
+            use parser::GeneralParser;
+            GeneralParser::new(spec.clone(), LexerVec::new(vec![src.to_string()])).parse()
+        };
+        assert_eq!(parse("x"), Ok(Node::Simple("x".to_string())));
+        assert_eq!(parse("y"), Err(ParseError::MissingRule{token: "y".to_string(), ty: "unimplemented".into()}));
+        //Filled tokens are listed like registered ones
+        assert_eq!(spec.to_string(), "ParserSpec:\n\
+            \x20   null x: bp 0\n\
+            \x20   null y: bp 0\n\
+            \x20   null (: bp 0\n\
+            \x20   null -: bp 10, rbp 15\n");
+    }
+
+    #[test]
+    fn test_consumed_kinds_by_key() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_null_assoc("y", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_assoc("+", PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        let tokens: Vec<String> = ["x", "+", "y", "+", "x"].iter().map(|s| s.to_string()).collect();
+        let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+        parser.enable_kind_counts();
+        parser.parse().unwrap();
+        //Counted by TextKey's key, the text, rather than the enum variant
+        let kinds = parser.consumed_kinds();
+        assert_eq!(kinds.get("x"), Some(&2));
+        assert_eq!(kinds.get("y"), Some(&1));
+        assert_eq!(kinds.get("+"), Some(&2));
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_group("(", ")").unwrap();
+        spec.set_max_nesting(3);
+        let spec = spec.freeze();
+        let parse = |depth: usize| {
+            let tokens = (0..depth).map(|_| "(")
+                .chain(Some("x"))
+                .chain((0..depth).map(|_| ")"))
+                .map(String::from);
+            GeneralParser::new(spec.clone(), LexerVec::new(tokens)).parse()
+        };
+        assert_eq!(parse(3), Ok(Node::Simple("x".to_string())));
+        assert_eq!(parse(4), Err(ParseError::NestingTooDeep{limit: 3}));
+        //Unlike UnexpectedEof, hit before the input is read to its end
+        assert_eq!(parse(10_000), Err(ParseError::NestingTooDeep{limit: 3}));
+    }
+
+    #[test]
+    fn test_delimiter_pairs() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_group("(", ")").unwrap();
+        spec.add_group("[", "]").unwrap();
+        spec.add_delimiters("{", "}");
+        assert_eq!(spec.closing_for(&"{".to_string()), Some(&"}".to_string()));
+        assert_eq!(spec.closing_for(&"x".to_string()), None);
+        let spec = spec.freeze();
+        let parser = |src: &str| GeneralParser::new(spec.clone(), LexerVec::new(src.chars().map(|c| c.to_string())));
+
+        assert_eq!(parser("([x])").parse(), Ok(Node::Simple("x".to_string())));
+        assert_eq!(parser("([x)]").parse(), Err(ParseError::MismatchedDelimiter{
+            open: "[".to_string(), expected: "]".to_string(), found: ")".to_string()
+        }));
+
+        let mut braces = parser("}]");
+        assert_eq!(braces.consume_matching(&"{".to_string()), Ok("}".to_string()));
+        assert_eq!(braces.consume_matching(&"(".to_string()), Err(ParseError::MismatchedDelimiter{
+            open: "(".to_string(), expected: ")".to_string(), found: "]".to_string()
+        }));
+        assert_eq!(braces.consume_matching(&"x".to_string()), Err(ParseError::MissingRule{token: "x".to_string(), ty: "Delimiter".into()}));
+    }
+
+    #[test]
+    fn test_duplicate_group_changes_nothing() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_null_assoc("a", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_group("(", ")").unwrap();
+        assert!(spec.add_group("(", "]").is_err());
+        assert_eq!(spec.closing_for(&"(".to_string()), Some(&")".to_string()));
+        assert!(!spec.is_closing(&"]".to_string()));
+
+        let mut parser = GeneralParser::new(spec, LexerVec::new(vec!["(", "a", ")"].into_iter().map(String::from)));
+        assert_eq!(parser.parse(), Ok(Node::Simple("a".to_string())));
+    }
+
+    #[test]
+    fn test_binding_power_queries() {
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        spec.add_prefix("-", PrecedenceLevel::Root, PrecedenceLevel::Third, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_left_right_assoc("^", PrecedenceLevel::Second, PrecedenceLevel::Third, |_, _, _, node| Ok(node)).unwrap();
+
+        assert_eq!(spec.null_bp(&"-".to_string()), Some(PrecedenceLevel::Root));
+        assert_eq!(spec.left_bp(&"^".to_string()), Some((PrecedenceLevel::Second, PrecedenceLevel::Third)));
+        assert_eq!(spec.null_bp(&"^".to_string()), None);
+        assert_eq!(spec.left_bp(&"-".to_string()), None);
+    }
+
+    #[test]
+    fn test_custom_key_and_hasher() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec = ParserSpec::with_key_and_hasher(TextKey, BuildHasherDefault::<DefaultHasher>::default());
+        spec.add_null_assoc("a", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_null_assoc("b", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(format!("b!{}", token)))).unwrap();
+        spec.add_left_assoc("+", PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+
+        let tokens = vec!["a", "+", "b"].into_iter().map(String::from);
+        let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+        assert_eq!(parser.parse(), Ok(Node::Composite{token: "+".to_string(), children: vec![
+            Node::Simple("a".to_string()), 
+            Node::Simple("b!b".to_string())
+        ], span: None}));
+    }
+
+    #[test]
+    fn test_frozen_spec_shared_by_parsers() {
+        use lexer::LexerVec;
+        use parser::GeneralParser;
+
+        let mut spec: ParserSpec<String, TextKey> = ParserSpec::with_key(TextKey);
+        for name in &["a", "b"] {
+            spec.add_null_assoc(*name, PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        }
+        spec.add_left_assoc("+", PrecedenceLevel::First, |parser, token, lbp, node| {
+            Ok(Node::Composite{token, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        let frozen = spec.freeze();
+        assert_eq!(frozen.left_bp(&"+".to_string()), Some((PrecedenceLevel::First, PrecedenceLevel::First)));
+
+        let parsers: Vec<_> = (0..4).map(|_| {
+            let tokens = vec!["a", "+", "b"].into_iter().map(String::from);
+            GeneralParser::new(frozen.clone(), LexerVec::new(tokens))
+        }).collect();
+        //One reference held here, one by each parser
+        assert_eq!(Arc::strong_count(&frozen.spec), 5);
+        for mut parser in parsers {
+            assert_eq!(parser.parse(), Ok(Node::Composite{token: "+".to_string(), children: vec![
+                Node::Simple("a".to_string()), 
+                Node::Simple("b".to_string())
+            ], span: None}));
+        }
+        assert_eq!(Arc::strong_count(&frozen.spec), 1);
+    }
+
+    //Keys String tokens by their text, so each operator gets its own rule
+    #[derive(Clone)]
+    struct TextKey;
+
+    impl KeyFn<String> for TextKey {
+        type Key = String;
+
+        fn key(&self, token: &String) -> String {
+            token.clone()
+        }
+    }
 }
\ No newline at end of file
@@ -27,14 +27,263 @@
 //! 
 //! The reason for each trait is as follows:
 //!  * Clone - This is a useful utility trait to implement. It makes it easier to 
-//!            build an Abstract Syntax Tree without dealing with references and 
-//!            lifetimes. 
+//!    build an Abstract Syntax Tree without dealing with references and 
+//!    lifetimes. 
 //!  * Debug - Necessary impl for failure::Fail trait
 //!  * Display - Necessary impl for failure::Fail trait
 //!  * PartialEq - Necessary impl for the consume method in GeneralParser
+//! 
+//! ## Rule keys
+//! ParserSpec and GeneralParser look rules up by a key extracted from each token
+//! by a KeyFn. The default, DiscriminantKey, uses the enum variant and ignores 
+//! any payload, so `Ident("a")` and `Ident("b")` share a rule. 
+//! 
+//! KindedToken<K, V> splits a token into a kind `K` (which picks the rule) and 
+//! a lexeme `V` (the matched text), keyed by KindKey. With it the token kind 
+//! can be a plain fieldless enum instead of carrying Strings in its variants.
+//! 
+//! NormalizedKey keys tokens on a normalized form of themselves, for 
+//! case-insensitive keywords and the like: with a lowercasing function, `If` 
+//! and `IF` share the rule registered for `if`. 
+//! 
+//! ## Token matchers
+//! Parser::consume compares with `==`, so what matches depends on the token's 
+//! PartialEq, which may or may not look at payloads. Parser::consume_if takes 
+//! a TokenMatcher instead, which says which one is meant: Exact compares whole 
+//! values, SameKind only the enum variant, Normalized compares normalized 
+//! forms (as NormalizedKey does), and any `Fn(&T) -> bool` closure works too.
 
-use std::fmt::{Debug, Display};
+use std::fmt;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::mem::{discriminant, Discriminant};
 
 pub trait Token:  Clone + Debug + Display + PartialEq  {}
 
 impl<T> Token for T where T:  Clone + Debug + Display + PartialEq {}
+
+/// Extracts the key that a token's syntax rules are stored under. 
+pub trait KeyFn<T>: Clone + Send + Sync + 'static {
+    type Key: Clone + Eq + Hash + Send + Sync + 'static;
+    fn key(&self, token: &T) -> Self::Key;
+}
+
+/// Default KeyFn: keys on the enum variant, ignoring payloads. 
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct DiscriminantKey;
+
+impl<T: 'static> KeyFn<T> for DiscriminantKey {
+    type Key = Discriminant<T>;
+
+    fn key(&self, token: &T) -> Discriminant<T> {
+        discriminant(token)
+    }
+}
+
+/// KeyFn that keys tokens on `normalize(token)`, so tokens that normalize 
+/// alike share rules. For case-insensitive keywords over String tokens: 
+/// 
+/// ```rust
+/// # use prattle::spec::ParserSpec;
+/// # use prattle::token::NormalizedKey;
+/// let spec: ParserSpec<String, _> = ParserSpec::with_key(NormalizedKey(|token: &String| token.to_lowercase()));
+/// ```
+/// 
+/// Only rule lookups are affected; consume and friends still compare with 
+/// `==`, so use consume_if with Normalized to accept any spelling. 
+pub struct NormalizedKey<T, N>(pub fn(&T) -> N);
+
+impl<T, N> Clone for NormalizedKey<T, N> {
+    fn clone(&self) -> NormalizedKey<T, N> {
+        *self
+    }
+}
+
+impl<T, N> Copy for NormalizedKey<T, N> {}
+
+impl<T: 'static, N> KeyFn<T> for NormalizedKey<T, N> 
+    where N: Clone + Eq + Hash + Send + Sync + 'static
+{
+    type Key = N;
+
+    fn key(&self, token: &T) -> N {
+        (self.0)(token)
+    }
+}
+
+/// Decides whether a token is the one a parser is looking for. 
+pub trait TokenMatcher<T> {
+    fn matches(&self, token: &T) -> bool;
+}
+
+/// Matches tokens equal to the given one, payload included (as far as the 
+/// token's PartialEq looks at it). 
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Exact<T>(pub T);
+
+impl<T: PartialEq> TokenMatcher<T> for Exact<T> {
+    fn matches(&self, token: &T) -> bool {
+        *token == self.0
+    }
+}
+
+/// Matches tokens of the same enum variant as the given one, whatever their 
+/// payload: `SameKind(Ident(String::new()))` matches any identifier. 
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct SameKind<T>(pub T);
+
+impl<T> TokenMatcher<T> for SameKind<T> {
+    fn matches(&self, token: &T) -> bool {
+        discriminant(token) == discriminant(&self.0)
+    }
+}
+
+/// Matches tokens that normalize to the same value as the given one: 
+/// `Normalized("then".to_string(), |t| t.to_lowercase())` matches `Then` and 
+/// `THEN`. See NormalizedKey. 
+pub struct Normalized<T, N>(pub T, pub fn(&T) -> N);
+
+impl<T, N: PartialEq> TokenMatcher<T> for Normalized<T, N> {
+    fn matches(&self, token: &T) -> bool {
+        (self.1)(token) == (self.1)(&self.0)
+    }
+}
+
+impl<T, F: Fn(&T) -> bool> TokenMatcher<T> for F {
+    fn matches(&self, token: &T) -> bool {
+        self(token)
+    }
+}
+
+/// A token split into the kind used to pick syntax rules and the lexeme 
+/// that was matched. 
+#[derive(Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct KindedToken<K, V> {
+    pub kind: K, 
+    pub lexeme: V,
+}
+
+impl<K, V> KindedToken<K, V> {
+    pub fn new(kind: K, lexeme: impl Into<V>) -> KindedToken<K, V> {
+        KindedToken {
+            kind, 
+            lexeme: lexeme.into()
+        }
+    }
+}
+
+///Shows as `Kind(lexeme)`, so the kind only needs to derive Debug. 
+impl<K: Debug, V: Display> Display for KindedToken<K, V> {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{:?}({})", self.kind, self.lexeme)
+    }
+}
+
+/// KeyFn for KindedToken: keys on the kind, ignoring the lexeme. 
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct KindKey;
+
+impl<K, V> KeyFn<KindedToken<K, V>> for KindKey 
+    where K: Clone + Eq + Hash + Send + Sync + 'static
+{
+    type Key = K;
+
+    fn key(&self, token: &KindedToken<K, V>) -> K {
+        token.kind.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use prelude::*;
+
+    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+    enum Kind { Ident, Add }
+
+    #[test]
+    fn test_kind_key_ignores_lexeme() {
+        let a = KindedToken::<Kind, String>::new(Kind::Ident, "a");
+        let b = KindedToken::<Kind, String>::new(Kind::Ident, "b");
+        assert_eq!(KindKey.key(&a), KindKey.key(&b));
+        assert!(KindKey.key(&a) != KindKey.key(&KindedToken::<Kind, String>::new(Kind::Add, "a")));
+        assert_eq!(a.to_string(), "Ident(a)");
+    }
+
+    //Payloads are only there to be ignored
+    #[allow(dead_code)]
+    #[derive(Clone, Debug)]
+    enum Word { Ident(String), Number(u32) }
+
+    //Payload-blind equality, as some token types implement it
+    impl PartialEq for Word {
+        fn eq(&self, other: &Word) -> bool {
+            discriminant(self) == discriminant(other)
+        }
+    }
+
+    #[test]
+    fn test_exact_matcher() {
+        assert!(Exact(3).matches(&3));
+        assert!(!Exact(3).matches(&4));
+        assert!(Exact("if".to_string()).matches(&"if".to_string()));
+        //Exact is only as exact as PartialEq
+        assert!(Exact(Word::Ident("a".into())).matches(&Word::Ident("b".into())));
+    }
+
+    #[test]
+    fn test_same_kind_matcher() {
+        let ident = SameKind(Word::Ident(String::new()));
+        assert!(ident.matches(&Word::Ident("a".into())));
+        assert!(!ident.matches(&Word::Number(1)));
+        assert!(SameKind(Some(0)).matches(&Some(5)));
+        assert!(!SameKind(Some(0)).matches(&None));
+        assert!((|n: &u32| *n > 2).matches(&3));
+    }
+
+    #[test]
+    fn test_normalized_keywords_share_a_rule() {
+        let lowercase: fn(&String) -> String = |token| token.to_lowercase();
+        let mut spec = ParserSpec::with_key(NormalizedKey(lowercase));
+        spec.add_null_assoc("x", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).unwrap();
+        spec.add_null_assoc("if", PrecedenceLevel::Root, move |parser, token, rbp| {
+            let cond = parser.parse_expr(rbp)?;
+            parser.consume_if(&Normalized("then".to_string(), lowercase))?;
+            Ok(Node::Composite{token, children: vec![cond, parser.parse_expr(rbp)?], span: None})
+        }).unwrap();
+        //Normalizes to a registered key: a duplicate
+        assert!(spec.add_null_assoc("IF", PrecedenceLevel::Root, |_, token, _| Ok(Node::Simple(token))).is_err());
+
+        let words = |src: &str| LexerVec::new(src.split(' ').map(String::from).collect::<Vec<_>>());
+        let spec = spec.freeze();
+        for src in &["if x then x", "IF x THEN x", "If x Then x"] {
+            let mut parser = GeneralParser::new(spec.clone(), words(src));
+            let tree = parser.parse().unwrap();
+            assert_eq!(tree.token().map(String::as_str), src.split(' ').next());
+            assert_eq!(tree.children(), &[Node::Simple("x".to_string()), Node::Simple("x".to_string())]);
+        }
+    }
+
+    #[test]
+    fn test_parse_kinded_tokens() {
+        type Tk = KindedToken<Kind, String>;
+        let mut spec = ParserSpec::with_key(KindKey);
+        spec.add_null_assoc(Tk::new(Kind::Ident, ""), PrecedenceLevel::Root, |_, tk, _| {
+            Ok(Node::Simple(tk))
+        }).unwrap();
+        spec.add_left_assoc(Tk::new(Kind::Add, "+"), PrecedenceLevel::First, |parser, tk, lbp, node| {
+            Ok(Node::Composite{token: tk, children: vec![node, parser.parse_expr(lbp)?], span: None})
+        }).unwrap();
+        //Same kind, different lexeme: still a duplicate registration
+        assert!(spec.add_null_assoc(Tk::new(Kind::Ident, "x"), PrecedenceLevel::Root, |_, tk, _| {
+            Ok(Node::Simple(tk))
+        }).is_err());
+
+        let tokens = vec![Tk::new(Kind::Ident, "x"), Tk::new(Kind::Add, "+"), Tk::new(Kind::Ident, "y")];
+        let mut parser = GeneralParser::new(spec, LexerVec::new(tokens));
+        assert_eq!(parser.parse(), Ok(Node::Composite{token: Tk::new(Kind::Add, "+"), children: vec![
+            Node::Simple(Tk::new(Kind::Ident, "x")), 
+            Node::Simple(Tk::new(Kind::Ident, "y")), 
+        ], span: None}));
+    }
+}
\ No newline at end of file